@@ -0,0 +1,199 @@
+// Throughput benchmarks for move generation, board copies, playouts and search on fixed
+// positions, run with `cargo bench` or `cargo bench -- [filter] [--time <seconds>]`
+use std::env;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use reversi::clock::MoveBudget;
+use reversi::perft::perft;
+use reversi::playout::{policy_from_name, PlayoutPolicies, Uniform};
+use reversi::tree::PuctConfig;
+use reversi::{random_playout, Board, SearchProgress, Searcher};
+
+/**
+ * Positions every benchmark runs on, in the Edax/obf format with the Player (X) to move
+ */
+const POSITIONS: [(&str, &str); 3] = [
+    ("start", "---------------------------OX------XO--------------------------- X"),
+    ("midgame", "----X------XX--O--XXOXO---XXXOX---XXOXO----OX-O---O-X----------- X"),
+    ("endgame", "-X--OX----XOXXX--XOOOXX---OOXOXOOOOOOXXOOOXOOOXOOOOOOO-O-O-X-OOO X")
+];
+
+/**
+ * Playout policies benchmarked, by their command line names
+ */
+const POLICIES: [&str; 6] = ["uniform", "maxtile", "epsilon-greedy", "corner-first", "mobility", "softmax"];
+
+/**
+ * Depth of the perft move generation benchmark
+ */
+const PERFT_DEPTH: u32 = 4;
+
+/**
+ * One benchmark result, printed as a line of JSON
+ *
+ *      group       -   what is measured: perft, moves, clone, playout or search
+ *      name        -   variant within the group, e.g. the playout policy
+ *      position    -   name of the position from POSITIONS
+ *      unit        -   what count counts, e.g. leaves or playouts
+ *      count       -   units done in the measured time
+ *      seconds     -   measured time
+ *      per_second  -   count / seconds
+ */
+#[derive(Serialize)]
+struct BenchResult {
+    group: &'static str,
+    name: String,
+    position: &'static str,
+    unit: &'static str,
+    count: u64,
+    seconds: f64,
+    per_second: f64
+}
+
+/**
+ * Runs f repeatedly, after one untimed warm-up run, until target has passed
+ *
+ *  Parameters:
+ *      f   -   does some work and returns how many units it did
+ *
+ *  @returns:   the units done and the time they took
+ */
+fn measure(target: Duration, mut f: impl FnMut() -> u64) -> (u64, Duration) {
+    f();
+
+    let start = Instant::now();
+    let mut count = 0;
+    while start.elapsed() < target {
+        count += f();
+    }
+
+    (count, start.elapsed())
+}
+
+/**
+ * Prints a result as JSON on stdout and readably on stderr, so the JSON lines can be
+ * redirected to a file for comparing runs
+ */
+fn report(group: &'static str, name: &str, position: &'static str, unit: &'static str, (count, elapsed): (u64, Duration)) {
+    let seconds = elapsed.as_secs_f64();
+    let result = BenchResult {
+        group,
+        name: name.to_string(),
+        position,
+        unit,
+        count,
+        seconds,
+        per_second: if seconds > 0.0 { count as f64 / seconds } else { 0.0 }
+    };
+
+    eprintln!("{:<8} {:<15} {:<8} {:>14.0} {}/s", group, name, position, result.per_second, unit);
+    println!("{}", serde_json::to_string(&result).unwrap_or_default());
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut target = Duration::from_secs(1);
+    let mut filter: Option<&str> = None;
+
+    // cargo bench passes --bench, which is ignored along with any other flags
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--time" => {
+                match args.get(i + 1).and_then(|t| t.parse::<f64>().ok()) {
+                    Some(secs) if secs > 0.0 => target = Duration::from_secs_f64(secs),
+                    _ => {
+                        println!("ERROR: --time expects a number of seconds");
+                        return;
+                    }
+                }
+                i += 1;
+            },
+            arg if !arg.starts_with("--") => filter = Some(arg),
+            _ => ()
+        }
+        i += 1;
+    }
+    let selected = |group: &str, name: &str| filter.is_none_or(|f| group.contains(f) || name.contains(f));
+
+    for (position, obf) in POSITIONS.iter() {
+        let board = match Board::from_obf(obf) {
+            Ok(b) => b,
+            Err(e) => {
+                println!("ERROR: position {}: {}", position, e);
+                return;
+            }
+        };
+
+        if selected("perft", "perft") {
+            report("perft", &format!("depth {}", PERFT_DEPTH), position, "leaves",
+                measure(target, || perft(black_box(&board), PERFT_DEPTH)));
+        }
+
+        // Playing each available move, including the board copy it is played on
+        if selected("moves", "moves") {
+            let val: u8 = if board.is_player_turn() { 1 } else { 2 };
+            report("moves", "ins", position, "moves", measure(target, || {
+                for action in board.get_available_actions(false) {
+                    let mut child = board.clone();
+                    child.ins(action, val, false);
+                    black_box(&child);
+                }
+                board.get_available_actions(false).len() as u64
+            }));
+        }
+
+        if selected("clone", "clone") {
+            report("clone", "board", position, "clones", measure(target, || {
+                black_box(black_box(&board).clone());
+                1
+            }));
+        }
+
+        for name in POLICIES.iter() {
+            if !selected("playout", name) {
+                continue;
+            }
+
+            let policy = match policy_from_name(name) {
+                Ok(p) => p,
+                Err(e) => {
+                    println!("ERROR: {}", e);
+                    return;
+                }
+            };
+            let policies = PlayoutPolicies { player: policy.as_ref(), cpu: policy.as_ref() };
+
+            report("playout", name, position, "playouts", measure(target, || {
+                let mut b = board.clone();
+                black_box(random_playout(&mut b, &policies, None, false));
+                1
+            }));
+        }
+
+        // A single search of the target time each, since the searches time themselves. A PUCT
+        // simulation adds one node to the tree and plays out from it
+        let searches = [("flat", None, "playouts"), ("puct", Some(PuctConfig::default()), "simulations")];
+        for (name, puct, unit) in searches.iter() {
+            if !selected("search", name) {
+                continue;
+            }
+
+            // The callback keeps the flat search from printing its progress dots
+            let quiet = |_: &SearchProgress| ();
+            let searcher = Searcher {
+                policies: PlayoutPolicies { player: &Uniform, cpu: &Uniform },
+                network: None,
+                puct: puct.clone(),
+                progress: Some(&quiet),
+                stop: None
+            };
+
+            let result = searcher.search(&board, &MoveBudget { target, maximum: target }, false).unwrap();
+            report("search", name, position, unit, (result.playouts as u64, result.elapsed));
+        }
+    }
+}
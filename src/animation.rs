@@ -0,0 +1,258 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+
+use gif::{Encoder, Frame, Repeat};
+
+use crate::{flipped, Board, GameRecord};
+
+/**
+ * Colours of the rendered frames, indexed by the pixels
+ */
+const PALETTE: [u8; 24] = [
+    0xff, 0xff, 0xff,   // background
+    0x2e, 0x7d, 0x32,   // board
+    0x1b, 0x3d, 0x1d,   // grid lines
+    0x11, 0x11, 0x11,   // Player's discs
+    0xf5, 0xf5, 0xf5,   // CPU's discs
+    0xe5, 0x39, 0x35,   // last move marker
+    0x33, 0x33, 0x33,   // text
+    0xff, 0x98, 0x00    // rings around flipped discs
+];
+const BACKGROUND: u8 = 0;
+const BOARD: u8 = 1;
+const LINE: u8 = 2;
+const PLAYER: u8 = 3;
+const CPU: u8 = 4;
+const LAST_MOVE: u8 = 5;
+const TEXT: u8 = 6;
+const FLIPPED: u8 = 7;
+
+/**
+ * 3x5 pixel glyphs for the coordinates and the score, one row per byte, the top row first
+ */
+const FONT: [(char, [u8; 5]); 18] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('A', [0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', [0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('G', [0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101])
+];
+
+/**
+ * Size and timing of a rendered game
+ *
+ *      square      -   width and height of a square in pixels
+ *      delay       -   time each move is shown, in hundredths of a second
+ *      final_delay -   time the final position is shown before the animation loops
+ */
+#[derive(Clone)]
+pub struct GifOptions {
+    pub square: u16,
+    pub delay: u16,
+    pub final_delay: u16
+}
+
+impl Default for GifOptions {
+
+    fn default() -> GifOptions {
+        GifOptions {
+            square: 40,
+            delay: 80,
+            final_delay: 300
+        }
+    }
+}
+
+/**
+ * Indexed pixels of one frame
+ */
+struct Canvas {
+    width: u16,
+    height: u16,
+    pixels: Vec<u8>
+}
+
+impl Canvas {
+
+    fn new(width: u16, height: u16) -> Canvas {
+        Canvas { width, height, pixels: vec![BACKGROUND; width as usize * height as usize] }
+    }
+
+    fn rect(&mut self, x: u16, y: u16, w: u16, h: u16, colour: u8) {
+        for py in y..(y + h).min(self.height) {
+            for px in x..(x + w).min(self.width) {
+                self.pixels[py as usize * self.width as usize + px as usize] = colour;
+            }
+        }
+    }
+
+    /**
+     * Fills the pixels whose centres are between inner and outer from (cx, cy), a disc if
+     * inner is 0
+     */
+    fn ring(&mut self, cx: f32, cy: f32, inner: f32, outer: f32, colour: u8) {
+        let top = (cy - outer).max(0.0) as u16;
+        let left = (cx - outer).max(0.0) as u16;
+
+        for py in top..((cy + outer).ceil() as u16).min(self.height) {
+            for px in left..((cx + outer).ceil() as u16).min(self.width) {
+                let (dx, dy) = (px as f32 + 0.5 - cx, py as f32 + 0.5 - cy);
+                let d2 = dx * dx + dy * dy;
+                if d2 <= outer * outer && d2 >= inner * inner {
+                    self.pixels[py as usize * self.width as usize + px as usize] = colour;
+                }
+            }
+        }
+    }
+
+    /**
+     * Writes text centred on (cx, cy), characters missing from FONT are left blank
+     */
+    fn text(&mut self, cx: u16, cy: u16, text: &str, scale: u16, colour: u8) {
+        let advance = 4 * scale;
+        let width = advance * text.chars().count() as u16 - scale;
+        let x0 = cx.saturating_sub(width / 2);
+        let y0 = cy.saturating_sub(5 * scale / 2);
+
+        for (i, c) in text.chars().enumerate() {
+            let glyph = match FONT.iter().find(|(g, _)| *g == c) {
+                Some((_, rows)) => rows,
+                None => continue
+            };
+            for (row, bits) in glyph.iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        self.rect(x0 + i as u16 * advance + col * scale, y0 + row as u16 * scale, scale, scale, colour);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Draws a position with a score bar underneath
+ *
+ *  Parameters:
+ *      last_move   -   square to mark as the move that led to the position
+ *      flipped     -   squares of the discs flipped by that move
+ */
+fn render(b: &Board, last_move: Option<u8>, flipped: &[u8], options: &GifOptions) -> Canvas {
+    let (width, height) = b.size();
+    let sq = options.square;
+    let margin = sq / 2;
+    let scale = (sq / 16).max(1);
+    let board_width = sq * width as u16;
+    let board_height = sq * height as u16;
+    let mut canvas = Canvas::new(margin * 2 + board_width, margin * 2 + board_height + sq);
+
+    canvas.rect(margin, margin, board_width, board_height, BOARD);
+    for i in 0..=width as u16 {
+        canvas.rect(margin + i * sq, margin, 1, board_height, LINE);
+    }
+    for i in 0..=height as u16 {
+        canvas.rect(margin, margin + i * sq, board_width, 1, LINE);
+    }
+
+    for col in 0..width as u16 {
+        let letter = (b'A' + col as u8) as char;
+        canvas.text(margin + col * sq + sq / 2, margin / 2, &letter.to_string(), scale, TEXT);
+    }
+    for row in 0..height as u16 {
+        canvas.text(margin / 2, margin + row * sq + sq / 2, &(row + 1).to_string(), scale, TEXT);
+    }
+
+    let radius = sq as f32 * 0.42;
+    for (pos, square) in (0_u8..).zip(b.squares().iter()) {
+        let cx = (margin + (pos % width) as u16 * sq) as f32 + sq as f32 / 2.0;
+        let cy = (margin + (pos / width) as u16 * sq) as f32 + sq as f32 / 2.0;
+
+        match square {
+            1 => canvas.ring(cx, cy, 0.0, radius, PLAYER),
+            2 => canvas.ring(cx, cy, 0.0, radius, CPU),
+            _ => ()
+        }
+        if flipped.contains(&pos) {
+            canvas.ring(cx, cy, radius - scale as f32 * 1.5, radius, FLIPPED);
+        }
+        if last_move == Some(pos) {
+            canvas.ring(cx, cy, 0.0, sq as f32 * 0.1, LAST_MOVE);
+        }
+    }
+
+    // Score bar: a disc of each colour followed by its count
+    let (player_score, cpu_score) = b.get_score();
+    let bar_y = (margin + board_height) as f32 + (margin + sq) as f32 / 2.0;
+    let quarter = (margin * 2 + board_width) as f32 / 4.0;
+    for (i, (colour, score)) in [(PLAYER, player_score), (CPU, cpu_score)].iter().enumerate() {
+        let x = quarter * (1 + 2 * i) as f32;
+        canvas.ring(x - sq as f32 * 0.5, bar_y, 0.0, sq as f32 * 0.3, LINE);
+        canvas.ring(x - sq as f32 * 0.5, bar_y, 0.0, sq as f32 * 0.3 - 1.0, *colour);
+        canvas.text((x + sq as f32 * 0.3) as u16, bar_y as u16, &score.to_string(), scale * 2, TEXT);
+    }
+
+    canvas
+}
+
+/**
+ * Writes a game as an animated GIF with one frame per position, from the starting position to
+ * the final one, marking each move and the discs it flipped
+ */
+pub fn write_game_gif<W: Write>(record: &GameRecord, options: &GifOptions, out: W) -> io::Result<()> {
+    let mut board = record.start();
+    let mut frames = vec![render(&board, None, &[], options)];
+
+    for mv in record.moves() {
+        let before = board.clone();
+        match mv {
+            Some(pos) => {
+                let val: u8 = if board.is_player_turn() { 1 } else { 2 };
+                board.ins(*pos, val, false);
+            },
+            None => board.pass()
+        }
+
+        frames.push(render(&board, *mv, &flipped(&before, &board), options));
+    }
+
+    let (width, height) = (frames[0].width, frames[0].height);
+    let mut encoder = Encoder::new(out, width, height, &PALETTE).map_err(io::Error::other)?;
+    encoder.set_repeat(Repeat::Infinite).map_err(io::Error::other)?;
+
+    let last = frames.len() - 1;
+    for (n, canvas) in frames.iter().enumerate() {
+        let frame = Frame {
+            width,
+            height,
+            delay: if n == last { options.final_delay } else { options.delay },
+            buffer: Cow::Borrowed(&canvas.pixels),
+            ..Frame::default()
+        };
+        encoder.write_frame(&frame).map_err(io::Error::other)?;
+    }
+
+    Ok(())
+}
+
+/**
+ * Writes a game as an animated GIF file, see write_game_gif()
+ */
+pub fn save_game_gif(record: &GameRecord, path: &str, options: &GifOptions) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    write_game_gif(record, options, file)
+}
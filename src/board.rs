@@ -0,0 +1,770 @@
+// IndexSet provides an indexed HashSet to allow returning element by index
+// Used for getting random items from set in O(1) time so MCTS is more efficient
+// Docs: https://docs.rs/indexmap/1.5.0/indexmap/set/struct.IndexSet.html
+use indexmap::IndexSet;
+
+// Pretty board styling
+use crate::theme;
+use crate::theme::Theme;
+
+/** 
+ * Game Board Struct
+ * 
+ * Manages the board vector and the information about it, including...
+ *      - perimeter tiles
+ *      - whether it is the players turn
+ *      - available actions for both player and cpu
+ * 
+ * Board.board elements are u8 integers, which represent:
+ *      0 => Empty Square
+ *      1 => Player
+ *      2 => CPU
+*/
+#[derive(Clone)]
+pub struct Board {
+    width: u8,
+    height: u8,
+    board_size: u8,
+    board: Vec<u8>,
+    perimeter: IndexSet<u8>,
+    player_available_actions: IndexSet<u8>,
+    cpu_available_actions: IndexSet<u8>,
+    player_turn: bool
+}
+
+/**
+ * Board object functions
+ */
+impl Board {
+
+    /**
+     * Initializes a Reversi game board
+     * 
+     */
+    pub fn new(w: u8, h: u8) -> Board {
+
+        let size = w * h;
+        let mut player_actions: IndexSet<u8> = IndexSet::new();
+        let mut cpu_actions: IndexSet<u8> = IndexSet::new();
+        let mut perimeter_tiles: IndexSet<u8> = IndexSet::new();
+        let mut new_board = vec![0; (size).into()];
+        
+        new_board[28] = 1;
+        new_board[35] = 1;
+        new_board[27] = 2;
+        new_board[36] = 2;
+
+        player_actions.insert(26);
+        player_actions.insert(19);
+        player_actions.insert(37);
+        player_actions.insert(44);
+
+        cpu_actions.insert(29);
+        cpu_actions.insert(20);
+        cpu_actions.insert(34);
+        cpu_actions.insert(43);
+
+        perimeter_tiles.insert(18);
+        perimeter_tiles.insert(19);
+        perimeter_tiles.insert(20);
+        perimeter_tiles.insert(21);
+        perimeter_tiles.insert(26);
+        perimeter_tiles.insert(29);
+        perimeter_tiles.insert(34);
+        perimeter_tiles.insert(37);
+        perimeter_tiles.insert(42);
+        perimeter_tiles.insert(43);
+        perimeter_tiles.insert(44);
+        perimeter_tiles.insert(45);
+
+        Board {
+            width: w,
+            height: h,
+            board_size: size,
+            board: new_board, //must convert u8 type -> usize type
+            perimeter: perimeter_tiles,
+            player_available_actions: player_actions,
+            cpu_available_actions: cpu_actions,
+            player_turn: true // Player always takes the first turn
+        }
+    }
+
+    /**
+     * Sets up an 8x8 position from its squares and the side to move
+     *
+     *  Parameters:
+     *      squares     -   the 64 squares row by row from A1, 0 empty, 1 Player, 2 CPU
+     *      player_turn -   true if the Player (1) is to move
+     */
+    pub fn from_squares(squares: &[u8], player_turn: bool) -> Result<Board, String> {
+        if squares.len() != 64 {
+            return Err(format!("expected 64 squares, got {}", squares.len()));
+        }
+        if let Some(val) = squares.iter().find(|val| **val > 2) {
+            return Err(format!("invalid square value {}", val));
+        }
+
+        let mut board = Board::new(8, 8);
+        board.board = squares.to_vec();
+        board.player_turn = player_turn;
+        board.recompute();
+        Ok(board)
+    }
+
+    /**
+     * Parses a position in the Edax/obf board format: the 64 squares row by row from A1 as
+     * X or * (Player, Black), O (CPU, White) or - or . (empty), then the side to move, e.g.
+     *      ---------------------------OX------XO--------------------------- X
+     * Anything after a ; (e.g. obf move scores) is ignored.
+     */
+    pub fn from_obf(s: &str) -> Result<Board, String> {
+        let position = s.split(';').next().unwrap_or("");
+        let mut chars = position.chars().filter(|c| !c.is_whitespace());
+
+        let squares: Vec<u8> = chars.by_ref().take(64).map(|c| match c {
+            'X' | 'x' | '*' => Ok(1),
+            'O' | 'o' => Ok(2),
+            '-' | '.' => Ok(0),
+            _ => Err(format!("invalid square '{}' in position", c))
+        }).collect::<Result<_, _>>()?;
+        if squares.len() != 64 {
+            return Err(format!("expected 64 squares, got {}", squares.len()));
+        }
+
+        let player_turn = match (chars.next(), chars.next()) {
+            (Some('X'), None) | (Some('x'), None) | (Some('*'), None) => true,
+            (Some('O'), None) | (Some('o'), None) => false,
+            _ => return Err(String::from("expected the side to move (X or O) after the squares"))
+        };
+
+        Board::from_squares(&squares, player_turn)
+    }
+
+    /**
+     * Returns the position in the Edax/obf board format read by from_obf()
+     */
+    pub fn to_obf(&self) -> String {
+        let squares: String = self.board.iter().map(|val| match val {
+            1 => 'X',
+            2 => 'O',
+            _ => '-'
+        }).collect();
+
+        format!("{} {}", squares, if self.player_turn { 'X' } else { 'O' })
+    }
+
+    /**
+     * Sets a square to 0 (empty), 1 (Player) or 2 (CPU) without flipping any discs, e.g. to
+     * edit a position
+     *
+     *  @returns:   an error, leaving the board unchanged, if pos is off the board or val is not 0, 1 or 2
+     */
+    pub fn set_square(&mut self, pos: u8, val: u8) -> Result<(), String> {
+        if pos as usize >= self.board.len() {
+            return Err(format!("square {} is off the board", pos));
+        }
+        if val > 2 {
+            return Err(format!("invalid square value {}", val));
+        }
+
+        self.add(pos, val);
+        self.recompute();
+        Ok(())
+    }
+
+    /**
+     * Sets the side to move, true for the Player (1)
+     */
+    pub fn set_player_turn(&mut self, player_turn: bool) {
+        self.player_turn = player_turn;
+    }
+
+    /**
+     * Rebuilds the perimeter and both sides' available actions from the squares, rather than
+     * updating them move by move like ins()
+     */
+    fn recompute(&mut self) {
+        self.perimeter.clear();
+        self.player_available_actions.clear();
+        self.cpu_available_actions.clear();
+
+        // The perimeter is every empty square next to a disc
+        for pos in 0..self.board_size {
+            if self.board[pos as usize] == 0 && self.neighbours(pos).iter().any(|n| self.board[*n as usize] != 0) {
+                self.perimeter.insert(pos);
+            }
+        }
+
+        for player in 1..3 {
+            for tile in self.get_perimeter() {
+                self.check_tile_actions(tile, player, false);
+            }
+        }
+    }
+
+    /**
+     * Returns the squares around pos, without wrapping around the edges of the board
+     */
+    fn neighbours(&self, pos: u8) -> Vec<u8> {
+        let (row, col) = ((pos / self.width) as i16, (pos % self.width) as i16);
+
+        (-1..=1).flat_map(|dr| (-1..=1).map(move |dc| (row + dr, col + dc)))
+            .filter(|(r, c)| (*r, *c) != (row, col) && (0..self.height as i16).contains(r) && (0..self.width as i16).contains(c))
+            .map(|(r, c)| (r * self.width as i16 + c) as u8)
+            .collect()
+    }
+
+    /**
+     * Checks that the perimeter and available actions kept up to date by ins() match the ones
+     * worked out from scratch for the same squares
+     *
+     * @returns:    a description of the differences if they don't match
+     */
+    pub fn check_consistency(&self) -> Result<(), String> {
+        let mut expected = self.clone();
+        expected.recompute();
+
+        let mut errors = Vec::new();
+        let sets = [
+            ("perimeter", &self.perimeter, &expected.perimeter),
+            ("Player actions", &self.player_available_actions, &expected.player_available_actions),
+            ("CPU actions", &self.cpu_available_actions, &expected.cpu_available_actions)
+        ];
+        for (name, actual, expected) in sets.iter() {
+            let extra: Vec<String> = actual.difference(expected).map(|pos| convert_num(*pos)).collect();
+            let missing: Vec<String> = expected.difference(actual).map(|pos| convert_num(*pos)).collect();
+            if !extra.is_empty() || !missing.is_empty() {
+                errors.push(format!("{} has extra [{}] and is missing [{}]", name, extra.join(" "), missing.join(" ")));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("inconsistent board {}: {}", self.to_obf(), errors.join(", ")))
+        }
+    }
+
+    /**
+     * Print the board vec to the screen
+     * 
+     * Tiles are printed in the colours of the current theme, see theme::set_theme()
+     */
+    pub fn print(&self) {
+        self.print_annotated(&[]);
+    }
+
+    /**
+     * Print the board with labels on some of the empty squares, e.g. to rank moves
+     *
+     * Labelled squares show their label instead of the available action marker.
+     */
+    pub fn print_annotated(&self, labels: &[(u8, char)]) {
+
+        let theme: Theme = theme::current();
+        let (player_score, cpu_score): (u8, u8) = self.get_score();
+
+        println!("\n     {}", theme.bold("A B C D E F G H") );
+
+        for (count, i) in (0_u8..).zip(self.board.iter()) {
+            if count % self.width == 0 {
+                if count != 0 {
+                    let row_num: u8 = count / 8;
+                    print!("{}\n     ", theme.bold(&row_num.to_string()));
+                }else{  
+                    print!("     ")
+                }
+            }
+            let label = labels.iter().find(|(pos, _)| *pos == count).map(|(_, c)| c);
+            if i == &1 {
+                print!("{} ", theme.player_disc());
+            } else if i == &2 {
+                print!("{} ", theme.cpu_disc());
+            } else if let Some(c) = label {
+                print!("{} ", theme.highlight(&c.to_string()));
+            } else if self.player_available_actions.contains(&count) {
+                print!("{} ", theme.bold(&theme.action.to_string()));
+            } else { 
+                print!("{} ", theme.empty); 
+            }
+        }
+        print!("{}\n\n", theme.bold("8"));
+
+        println!("     Player: {}, CPU: {}\n", theme.paint_player(&player_score.to_string()), theme.paint_cpu(&cpu_score.to_string()));
+
+    }
+
+    /**
+     * Handles a piece being put onto the board
+     * 
+     * Adds to board -> flips pieces -> update perimeter -> updates available actions -> change turns
+     */
+    pub fn ins(&mut self, pos: u8, val: u8, debug: bool) {
+
+        // Add new tile to board
+        let pos_u: usize = match self.get_available_actions(debug).contains(&pos) {
+            false => {
+                println!("ERROR: {} is not a valid action", pos);
+                return;
+            },
+            true => pos.into()
+        };
+
+        self.board.splice(pos_u..pos_u+1, [val].iter().cloned());
+
+        let mut u: u8;
+        let mut tiles = Vec::new();
+
+        // Manages the direction of iteration
+        for direction in 0..8 {
+
+            // This part of the function iterates in all 8 directions from the tile, checking if any of
+            // the tiles in these directions will be flipped -> that is, they are...
+            //                      - adjacent to the newly placed tile, or
+            //                      - in a span of opposing tiles adjacent to the newly placed tile, and
+            //                      - has a tile on the other side of the opposing tiles that "sandwiches"
+            //                          them with no empty spaces inbetween
+
+            u = 1;
+            tiles.clear();
+
+            loop {
+
+                // Depending on direction, changes the formula for iteration
+                let new_pos: u8 = match get_new_pos(direction, pos, u, self.board_size) {
+                    None => break,
+                    Some(x) => x
+                };
+
+                let new_pos_usize: usize = new_pos.into();
+
+                let tile = self.board.get(new_pos_usize).unwrap();
+
+                // Refer to comment above for explanation
+                if tile != &val && tile != &0 {
+                    tiles.push(new_pos);
+                } else if tile == &val {
+                    // Only the tiles up to the first sandwiching tile are flipped
+                    for t in &tiles {
+                        self.add(*t, val);
+                    }
+                    break;
+                } else {
+                    tiles.clear();
+                    break;
+                }
+                
+                u += 1;
+            }
+        }
+
+        // Remove inserted tile from perimeter
+        self.perimeter.remove(&pos);
+
+        // Adds the empty squares around it to the perimeter IndexSet
+        for new_pos in self.neighbours(pos) {
+            if self.board[new_pos as usize] == 0 {
+                self.perimeter.insert(new_pos);
+            }
+        }
+
+        if debug { println!("{:?}", self.perimeter); }
+
+        // Update available actions
+        self.player_available_actions.remove(&pos);
+        self.cpu_available_actions.remove(&pos);
+
+        // For each player 1 and 2...
+        for player in 1..3 {
+            // For each tile in the perimeter
+            for tile in self.get_perimeter() {
+                // Check if that tile is an available action
+                self.check_tile_actions(tile, player, debug);
+            }
+        }
+
+        // Alternate turns
+        if self.player_turn {
+            if debug { println!("Player's turn"); }
+            self.player_turn = false
+        } else {
+            if debug { println!("CPU's turn"); }
+            self.player_turn = true
+        }
+
+        if debug {
+            println!("Player's Available Actions: {:?}", self.get_player_actions());
+            println!("CPU's Available Actions: {:?}", self.get_cpu_actions());
+
+            if let Err(e) = self.check_consistency() {
+                println!("ERROR: {}", e);
+            }
+        }
+
+        // Checks every move against the state worked out from scratch, at the cost of most
+        // of the move generation speed
+        #[cfg(feature = "consistency-checks")]
+        assert_eq!(self.check_consistency(), Ok(()));
+    }
+
+    /**
+     * Given a tile position it will check in all directions if it is an available option 
+     * for player with the input val (1 or 2), and adds it to or removes it from their actions
+     */
+    fn check_tile_actions(&mut self, pos: u8, val: u8, debug: bool){
+
+        let mut u: u8; // used as the iter for get_new_pos()
+        let mut tiles = Vec::new();
+        let mut available = false;
+
+        // Manages the direction of iteration
+        for direction in 0..8 {
+
+            u = 1;
+            tiles.clear();
+
+            loop {
+
+                // Depending on direction, changes the formula for iteration
+                let new_pos: u8 = match get_new_pos(direction, pos, u, self.board_size) {
+                    None => break,
+                    Some(x) => x
+                };
+
+                let new_pos_usize: usize = new_pos.into();
+                let tile = self.board.get(new_pos_usize).unwrap(); // Gets value from tile at new position
+
+                if tile != &val && tile != &0 {
+                    // If the tile is not the same color as inserted, add to tiles vec
+                    tiles.push(new_pos);
+                } else {
+                    // A tile the same color as the initial val with opposing tiles inbetween makes
+                    // this an available action, a blank tile means not in this direction
+                    available = tile == &val && !tiles.is_empty();
+                    break;
+                }
+                u += 1;
+
+            }
+
+            if available {
+                break;
+            }
+        }
+
+        // Decided after every direction, since one running off the board says nothing
+        let actions = if val == 1 { &mut self.player_available_actions } else { &mut self.cpu_available_actions };
+        if available {
+            if debug {
+                println!("Added {} to actions for player {}", pos, val);
+            }
+            actions.insert(pos);
+        } else {
+            if debug && actions.contains(&pos) {
+                println!("Removed {} from actions for player {}", pos, val);
+            }
+            actions.remove(&pos);
+        }
+    }
+
+    /**
+     * Returns a clone of the IndexSet of available actions depending on which players turn it is
+     * 
+     * Should only use this function to get the available actions, don't individually
+     * reference the player or cpu sets
+     */
+    pub fn get_available_actions(&self, debug: bool) -> IndexSet<u8> {
+        if self.player_turn {
+            let actions = self.get_player_actions();
+            if debug {
+                println!("Player Available Actions: {:?}", actions);
+            }  
+            actions
+        } else {
+            let actions = self.get_cpu_actions();
+            if debug {
+                println!("CPU Available Actions: {:?}", actions);
+            }
+            actions
+        }
+    }
+
+    /**
+     * Returns the Player's (1) available actions regardless of whose turn it is
+     */
+    pub fn get_player_actions(&self) -> IndexSet<u8> {
+        IndexSet::clone(&self.player_available_actions)
+    }
+
+    /**
+     * Returns the CPU's (2) available actions regardless of whose turn it is
+     */
+    pub fn get_cpu_actions(&self) -> IndexSet<u8> {
+        IndexSet::clone(&self.cpu_available_actions)
+    }
+
+    /**
+     * Returns true when the Player (1) is to move, false when the CPU (2) is
+     */
+    pub fn is_player_turn(&self) -> bool {
+        self.player_turn
+    }
+
+    /**
+     * Returns the (width, height) of the board
+     */
+    pub fn size(&self) -> (u8, u8) {
+        (self.width, self.height)
+    }
+
+    /**
+     * Returns the squares row by row from A1, 0 empty, 1 Player, 2 CPU
+     */
+    pub fn squares(&self) -> &[u8] {
+        &self.board
+    }
+
+    /**
+     * Passes the turn to the other player
+     *
+     * The game loop ends the game when either player runs out of actions, this is only
+     * used by protocol front-ends whose GUIs follow the standard passing rules.
+     */
+    pub fn pass(&mut self) {
+        self.player_turn = !self.player_turn;
+
+        #[cfg(feature = "consistency-checks")]
+        assert_eq!(self.check_consistency(), Ok(()));
+    }
+
+    /**
+     * Returns IndexSet of the tiles in the perimeter of the board pieces
+     */
+    pub fn get_perimeter(&self) -> IndexSet<u8> {
+        IndexSet::clone(&self.perimeter)
+    }
+
+    /**
+     * Checks whether the game has ended, which happens when either player has no actions
+     *
+     * @returns:    0 -> incomplete
+     *              1 -> player win
+     *              2 -> cpu win
+     *              3 -> draw
+     */
+    pub fn check_game_state(&self, debug: bool) -> u8 {
+        let player_actions = self.get_player_actions();
+        let cpu_actions = self.get_cpu_actions();
+
+        // GAME IS ENDED
+        if cpu_actions.is_empty() || player_actions.is_empty() {         
+
+            let (player_score, cpu_score): (u8, u8) = self.get_score();
+
+            if debug {
+                let theme: Theme = theme::current();
+                println!("  Player: {}, CPU: {}", theme.paint_player(&player_score.to_string()), theme.paint_cpu(&cpu_score.to_string()));
+            }
+            
+            if player_score > cpu_score {
+                1
+            } else if cpu_score > player_score {
+                2
+            } else {
+                3
+            }
+        }
+
+        else { 0 }
+
+    }
+
+    /**
+     * get_score() -> returns tuple containing current score for player and cpu
+     */
+    pub fn get_score(&self) -> (u8, u8) {
+        let mut count_player = 0;
+        let mut count_cpu = 0;
+
+        for i in 0..64 {
+            match self.board.get(i).unwrap() {
+                0 => continue,
+                1 => count_player += 1,
+                2 => count_cpu += 1,
+                _ => println!("Error Code: ID10T" )
+            }
+        }
+
+        (count_player, count_cpu)
+    }
+
+    /**
+     * Add value at position on board
+     * 
+     * val = 0: unused square
+     * val = 1: player piece
+     * val = 2: cpu piece
+     */
+    fn add(&mut self, pos: u8, val: u8) {
+        let pos_u: usize = pos.into();
+        self.board.splice(pos_u..(pos_u + 1), [val].iter().cloned());
+    }
+}
+
+/** 
+ * Returns a new position based on direction, initial pos, iteration, and board size
+ * Intended to be used in a loop (such as in the Board.ins() function)
+ * 
+ * @returns: Some(x) if new position is on board, or
+ * @returns: None if position overflows board
+ */
+pub fn get_new_pos(dir: u8, pos: u8, iter: u8, size: u8) -> Option<u8> {
+    match dir {
+
+        0 => { // Right
+            let position = pos + iter;
+            if position.is_multiple_of(8) {
+                None
+            } else {
+                Some(position)
+            }
+        },
+
+        1 => { // Left
+            pos.checked_sub(iter).filter(|x| x % 8 != 7)
+        },
+
+        2 => { // Down
+            let position = pos + (iter * 8);
+            if position < size {
+                Some(position)
+            } else {
+                None
+            }
+        },
+
+        3 => { // Up
+            pos.checked_sub(iter * 8)
+        },
+
+        4 => { // Up left: must check that doesn't % 8 = 7 and doesn't overflow
+            pos.checked_sub(iter * 8 + iter).filter(|x| x % 8 != 7)
+        },
+
+        5 => { // Up right: must check that doesn't % 8 = 0 and doesn't overflow
+            pos.checked_sub(iter * 8 - iter).filter(|x| !x.is_multiple_of(8))
+        },
+
+        6 => { // Down left: must check that doesnt % 8 = 7 and 
+            let position = pos + (iter * 8) - iter;
+            if position < size && position % 8 != 7 {
+                Some(position)
+            } else {
+                None
+            }
+        },
+
+        7 => { // Down right: must check that doesnt % 8 = 0 and 
+            let position = pos + (iter * 8) + iter;
+            if position < size && !position.is_multiple_of(8) {
+                Some(position)
+            } else {
+                None
+            }
+        },
+
+        _ => None
+    }
+}
+
+/**
+ * Convert 2d string index to vector index
+ * @params:     s: &str - len 2 string of char A-H followed by int 1-8
+ * @returns:    u8 position in 1d Vec
+ */
+pub fn convert_2d(s: &str) -> u8{
+
+    //Handle panic
+    let letter = s.chars().next().unwrap().to_ascii_lowercase();
+    let num = s.chars().nth(1).unwrap();
+
+    let col: u8 = match letter {
+        'a' => 0,
+        'b' => 1,
+        'c' => 2,
+        'd' => 3,
+        'e' => 4,
+        'f' => 5,
+        'g' => 6,
+        'h' => 7,
+        _   => 42
+    };
+
+    // Probably better way to do this.... but I couldn't find it
+    let row: u8 = match num {
+        '1' => 0,
+        '2' => 1,
+        '3' => 2,
+        '4' => 3,
+        '5' => 4,
+        '6' => 5,
+        '7' => 6,
+        '8' => 7,
+        _   => 42
+    };
+
+    row * 8 + col
+}
+
+/**
+ * Convert integer vector index into 2d string index
+ * Note: this function is the inverse of convert_2d()
+ * @params:     num: less than 64 valued integer representing 1d index of vector
+ * @returns:    String of values `[a-h][1-8]`
+ */
+pub fn convert_num(num: u8) -> String {
+
+    if num >= 64 {
+        println!("ERROR convert_num() -> input too large");
+        return String::from("ERR");
+    }
+
+    let letter: &str = match num % 8 {
+        0 => "A",
+        1 => "B",
+        2 => "C",
+        3 => "D",
+        4 => "E",
+        5 => "F",
+        6 => "G",
+        _ => "H"
+    };
+
+    format!("{}{}", letter, num / 8 + 1)
+}
+
+/**
+ * Parses a 2d string index such as "d3" from protocol input
+ * @returns:    Some(position in 1d Vec), or None if the string is not a square on the board
+ */
+pub fn parse_pos(s: &str) -> Option<u8> {
+    let mut chars = s.chars();
+
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(letter), Some(num), None) if ('a'..='h').contains(&letter.to_ascii_lowercase()) && ('1'..='8').contains(&num) => {
+            Some(convert_2d(s))
+        },
+        _ => None
+    }
+}
+
+/**
+ * Returns the squares whose discs changed colour between two positions, e.g. the discs
+ * flipped by a move
+ */
+pub fn flipped(before: &Board, after: &Board) -> Vec<u8> {
+    (0_u8..).zip(before.squares().iter().zip(after.squares().iter()))
+        .filter(|(_, (old, new))| **old != 0 && old != new)
+        .map(|(pos, _)| pos)
+        .collect()
+}
@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+/**
+ * Share of the remaining clock that is never allocated, to absorb overhead between moves
+ */
+const SAFETY_MARGIN: f64 = 0.05;
+
+/**
+ * How many times the target time a move may use when the top two candidates are close
+ */
+const MAX_EXTENSION: f64 = 2.5;
+
+/**
+ * The top two candidates are close when the second has at least this share of the best's count
+ */
+const CLOSE_RATIO: f64 = 0.9;
+
+/**
+ * Minimum number of iterations before the search may stop early
+ */
+const MIN_ITERATIONS: usize = 50;
+
+/**
+ * Game clock for one side
+ *
+ * Tracks the time left for the game, adds the increment after every move and splits
+ * the remaining time over the moves that are still to be played.
+ */
+pub struct TimeManager {
+    remaining: Duration,
+    increment: Duration
+}
+
+impl TimeManager {
+
+    pub fn new(total: Duration, increment: Duration) -> TimeManager {
+        TimeManager { remaining: total, increment }
+    }
+
+    /**
+     * Budget for the next move given the number of empty squares on the board
+     *
+     * The side to move plays about half of the remaining empties, so the usable time
+     * is split evenly over those moves plus the increment of this move.
+     */
+    pub fn allocate(&self, empties: u8) -> MoveBudget {
+        let usable = self.remaining.as_secs_f64() * (1.0 - SAFETY_MARGIN);
+        let moves_left = f64::from(empties.div_ceil(2).max(1));
+
+        let target = (usable / moves_left + self.increment.as_secs_f64()).min(usable);
+        let maximum = (target * MAX_EXTENSION).min(usable * 0.5).max(target);
+
+        MoveBudget {
+            target: Duration::from_secs_f64(target),
+            maximum: Duration::from_secs_f64(maximum)
+        }
+    }
+
+    /**
+     * Charges the time used for a move to the clock and adds the increment
+     */
+    pub fn record(&mut self, used: Duration) {
+        self.remaining = self.remaining.checked_sub(used).unwrap_or_default() + self.increment;
+    }
+
+    /**
+     * Returns the time left on the clock
+     */
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    /**
+     * Corrects the clock to the time left reported by a controller
+     */
+    pub fn set_remaining(&mut self, remaining: Duration) {
+        self.remaining = remaining;
+    }
+}
+
+/**
+ * Time budget of a single search
+ *
+ *      target      -   time the search normally stops at
+ *      maximum     -   hard limit, only reached when the top two candidates are close
+ */
+#[derive(Clone, Copy)]
+pub struct MoveBudget {
+    pub target: Duration,
+    pub maximum: Duration
+}
+
+impl MoveBudget {
+
+    /**
+     * Budget without a game clock, always searching for secs seconds at most
+     */
+    pub fn fixed(secs: u64) -> MoveBudget {
+        MoveBudget {
+            target: Duration::new(secs, 0),
+            maximum: Duration::new(secs, 0)
+        }
+    }
+
+    /**
+     * Decides whether the search should stop
+     *
+     *  Parameters:
+     *      elapsed     -   time searched so far
+     *      iterations  -   iterations done so far
+     *      best        -   count the best candidate is chosen by (wins or visits)
+     *      second      -   the same count for the runner-up
+     *
+     * Each iteration closes the gap between the two counts by at most one, so the search
+     * stops early when the runner-up could not catch the best candidate in the iterations
+     * left before the target time, and keeps going past the target up to the maximum while
+     * the top two candidates are close.
+     */
+    pub fn should_stop(&self, elapsed: Duration, iterations: usize, best: usize, second: usize) -> bool {
+        if elapsed >= self.maximum {
+            return true;
+        }
+
+        let close = second as f64 >= best as f64 * CLOSE_RATIO;
+        if elapsed >= self.target {
+            return !close;
+        }
+
+        if iterations < MIN_ITERATIONS || elapsed.as_secs_f64() <= 0.0 {
+            return false;
+        }
+
+        let rate = iterations as f64 / elapsed.as_secs_f64();
+        let iterations_left = rate * (self.target - elapsed).as_secs_f64();
+
+        (best - second) as f64 > iterations_left
+    }
+}
+
+/**
+ * Returns the largest and second largest counts, used for MoveBudget::should_stop()
+ */
+pub fn top_two<I: Iterator<Item = usize>>(counts: I) -> (usize, usize) {
+    let mut best = 0;
+    let mut second = 0;
+
+    for count in counts {
+        if count > best {
+            second = best;
+            best = count;
+        } else if count > second {
+            second = count;
+        }
+    }
+
+    (best, second)
+}
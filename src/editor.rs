@@ -0,0 +1,109 @@
+use std::io;
+
+use reversi::theme;
+use reversi::{parse_pos, Board};
+
+fn print_editor_help() {
+    let theme = theme::current();
+
+    println!("\nEditor commands:\n");
+    println!("  {}  -  put a Player (x) or CPU (o) disc on a square or empty it (-), e.g. 'd4 x'", theme.bold("<square> x|o|-"));
+    println!("  {}      -  set the side to move", theme.bold("turn x|o"));
+    println!("  {}         -  empty the board", theme.bold("clear"));
+    println!("  {}         -  go back to the starting position", theme.bold("reset"));
+    println!("  {}  -  load a position in the Edax/obf format (64 squares of X, O or -, then X or O to move)", theme.bold("load <position>"));
+    println!("  {}           -  print the position in the Edax/obf format", theme.bold("obf"));
+    println!("  {}          -  play a game from the position", theme.bold("play"));
+    println!("  {}          -  quit without playing", theme.bold("exit"));
+    println!();
+}
+
+/**
+ * Sets up a position from the terminal, starting from start
+ *
+ * Discs are placed without flipping anything, and the available actions are worked out
+ * from scratch after every change.
+ *
+ *  @returns:   the position to play from, or None if the editor was left without playing
+ */
+pub fn run(start: Board) -> Option<Board> {
+    let mut board = start;
+
+    println!("Position editor, enter 'help' for the commands");
+    show(&board);
+
+    loop {
+        println!("Edit: ");
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) | Err(_) => return None,
+            Ok(_) => ()
+        }
+
+        let words: Vec<&str> = input.split_whitespace().collect();
+        match words.as_slice() {
+            [square, value] if parse_pos(square).is_some() => {
+                let val: u8 = match *value {
+                    "x" | "X" => 1,
+                    "o" | "O" => 2,
+                    "-" | "." => 0,
+                    _ => {
+                        println!("ERROR: expected x, o or - after the square");
+                        continue;
+                    }
+                };
+                if let Err(e) = board.set_square(parse_pos(square).unwrap_or(0), val) {
+                    println!("ERROR: {}", e);
+                    continue;
+                }
+            },
+            ["turn", side] => match *side {
+                "x" | "X" => board.set_player_turn(true),
+                "o" | "O" => board.set_player_turn(false),
+                _ => {
+                    println!("ERROR: expected x or o after turn");
+                    continue;
+                }
+            },
+            ["clear"] => {
+                if let Ok(b) = Board::from_squares(&[0; 64], board.is_player_turn()) {
+                    board = b;
+                }
+            },
+            ["reset"] => board = Board::new(8, 8),
+            ["load", ..] => match Board::from_obf(input.trim_start()["load".len()..].trim()) {
+                Ok(b) => board = b,
+                Err(e) => {
+                    println!("ERROR: {}", e);
+                    continue;
+                }
+            },
+            ["obf"] => {
+                println!("{}", board.to_obf());
+                continue;
+            },
+            ["play"] => return Some(board),
+            ["help"] => {
+                print_editor_help();
+                continue;
+            },
+            ["exit"] => return None,
+            _ => {
+                println!("ERROR: Invalid entry, enter 'help' for the commands");
+                continue;
+            }
+        }
+
+        show(&board);
+    }
+}
+
+fn show(board: &Board) {
+    board.print();
+    println!("{} to move: {}", if board.is_player_turn() { "Player (x)" } else { "CPU (o)" }, board.to_obf());
+
+    if board.check_game_state(false) != 0 {
+        println!("A side has no available actions, so a game from here is already over");
+    }
+    println!();
+}
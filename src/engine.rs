@@ -0,0 +1,312 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::clock::MoveBudget;
+use crate::nn::Network;
+use crate::playout::{MaxTile, PlayoutPolicies, PlayoutPolicy, Uniform};
+use crate::tree::PuctConfig;
+use crate::{convert_num, Board, SearchProgress, SearchResult, Searcher};
+
+/**
+ * A Reversi AI that front-ends can drive without knowing how it searches
+ *
+ * The engine keeps its own copy of the game, either built up move by move with
+ * play_move() or replaced with set_position(), and chooses moves for the side to move.
+ * Moves are board positions and None is a pass.
+ */
+pub trait Engine {
+
+    /**
+     * Name of the engine, as reported to GUIs and controllers
+     */
+    fn name(&self) -> String;
+
+    /**
+     * Resets the engine to the starting position
+     */
+    fn new_game(&mut self);
+
+    /**
+     * Replaces the engine's position
+     */
+    fn set_position(&mut self, b: &Board);
+
+    /**
+     * Plays a move for the side to move in the engine's position
+     */
+    fn play_move(&mut self, mv: Option<u8>) -> Result<(), String>;
+
+    /**
+     * Searches the engine's position within the budget and returns the move to play for the
+     * side to move, or None if it has to pass. The move is not played.
+     */
+    fn choose_move(&mut self, budget: &MoveBudget) -> Option<u8>;
+
+    /**
+     * Returns a handle that stops a running choose_move() from another thread
+     */
+    fn stop_handle(&self) -> StopHandle;
+
+    /**
+     * Stops a running choose_move(), which then returns its best move so far. A stop issued
+     * before choose_move() is called stops that search straight away.
+     */
+    fn stop(&self) {
+        self.stop_handle().stop();
+    }
+
+    /**
+     * Returns the statistics of the last search, if the engine keeps any
+     */
+    fn statistics(&self) -> Option<&SearchResult>;
+
+    /**
+     * Turns printing extra debug statements during searches on or off
+     */
+    fn set_debug(&mut self, _debug: bool) {}
+}
+
+/**
+ * Shared flag used to stop an engine's search
+ */
+#[derive(Clone, Default)]
+pub struct StopHandle {
+    flag: Arc<AtomicBool>
+}
+
+impl StopHandle {
+
+    pub fn new() -> StopHandle {
+        StopHandle::default()
+    }
+
+    /**
+     * Asks the search to stop
+     */
+    pub fn stop(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    /**
+     * Clears the flag once a search has been stopped
+     */
+    pub fn reset(&self) {
+        self.flag.store(false, Ordering::Relaxed);
+    }
+
+    /**
+     * Returns the flag for Searcher::stop
+     */
+    pub fn flag(&self) -> &AtomicBool {
+        &self.flag
+    }
+}
+
+/**
+ * Callback given live statistics while an MctsEngine searches
+ */
+pub type ProgressCallback = Box<dyn Fn(&SearchProgress)>;
+
+/**
+ * The built-in Monte Carlo engine: flat Monte Carlo search, or PUCT tree search when given a
+ * PuctConfig, with a playout policy for each colour and an optional network
+ *
+ * A loaded network replaces the CPU's (2) playout policy.
+ */
+pub struct MctsEngine {
+    player_policy: Box<dyn PlayoutPolicy>,
+    cpu_policy: Box<dyn PlayoutPolicy>,
+    network: Option<Network>,
+    puct: Option<PuctConfig>,
+    progress: Option<ProgressCallback>,
+    board: Board,
+    statistics: Option<SearchResult>,
+    stop: StopHandle,
+    debug: bool
+}
+
+impl MctsEngine {
+
+    pub fn new(player_policy: Box<dyn PlayoutPolicy>, cpu_policy: Box<dyn PlayoutPolicy>) -> MctsEngine {
+        MctsEngine {
+            player_policy,
+            cpu_policy,
+            network: None,
+            puct: None,
+            progress: None,
+            board: Board::new(8, 8),
+            statistics: None,
+            stop: StopHandle::new(),
+            debug: false
+        }
+    }
+
+    /**
+     * Easy difficulty: uniformly random playouts
+     */
+    pub fn easy() -> MctsEngine {
+        MctsEngine::new(Box::new(Uniform), Box::new(Uniform))
+    }
+
+    /**
+     * Hard difficulty: the CPU's playout moves use the Max Tile Heuristic
+     */
+    pub fn hard() -> MctsEngine {
+        MctsEngine::new(Box::new(Uniform), Box::new(MaxTile))
+    }
+
+    /**
+     * Guides the search with a value/policy network
+     */
+    pub fn with_network(mut self, network: Network) -> MctsEngine {
+        self.network = Some(network);
+        self
+    }
+
+    /**
+     * Searches with PUCT tree search instead of flat Monte Carlo
+     */
+    pub fn with_puct(mut self, config: PuctConfig) -> MctsEngine {
+        self.puct = Some(config);
+        self
+    }
+
+    /**
+     * Calls progress with live statistics while searching
+     */
+    pub fn with_progress(mut self, progress: ProgressCallback) -> MctsEngine {
+        self.progress = Some(progress);
+        self
+    }
+}
+
+impl Engine for MctsEngine {
+
+    fn name(&self) -> String {
+        let search = if self.puct.is_some() { "puct" } else { "mcts" };
+        match &self.network {
+            Some(_) => format!("{} (network)", search),
+            None => format!("{} ({}/{})", search, self.player_policy.name(), self.cpu_policy.name())
+        }
+    }
+
+    fn new_game(&mut self) {
+        self.board = Board::new(8, 8);
+        self.statistics = None;
+    }
+
+    fn set_position(&mut self, b: &Board) {
+        self.board = b.clone();
+        self.statistics = None;
+    }
+
+    fn play_move(&mut self, mv: Option<u8>) -> Result<(), String> {
+        let actions = self.board.get_available_actions(false);
+
+        match mv {
+            Some(pos) if actions.contains(&pos) => {
+                let val: u8 = if self.board.is_player_turn() { 1 } else { 2 };
+                self.board.ins(pos, val, false);
+                Ok(())
+            },
+            None if actions.is_empty() => {
+                self.board.pass();
+                Ok(())
+            },
+            Some(_) => Err(String::from("illegal move")),
+            None => Err(String::from("illegal pass, the side to move has actions"))
+        }
+    }
+
+    fn choose_move(&mut self, budget: &MoveBudget) -> Option<u8> {
+        let cpu_policy: &dyn PlayoutPolicy = match &self.network {
+            Some(net) => net,
+            None => self.cpu_policy.as_ref()
+        };
+        let searcher = Searcher {
+            policies: PlayoutPolicies { player: self.player_policy.as_ref(), cpu: cpu_policy },
+            network: self.network.as_ref(),
+            puct: self.puct.clone(),
+            progress: self.progress.as_deref(),
+            stop: Some(self.stop.flag())
+        };
+
+        // The flag is cleared after the search so a stop issued just before it isn't lost
+        let result = searcher.search(&self.board, budget, self.debug);
+        self.stop.reset();
+
+        let result = result?;
+        let best = result.best;
+        self.statistics = Some(result);
+        Some(best)
+    }
+
+    fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+
+    fn statistics(&self) -> Option<&SearchResult> {
+        self.statistics.as_ref()
+    }
+
+    fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+    }
+}
+
+/**
+ * Result of a match, counted for the first engine
+ */
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct MatchScore {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32
+}
+
+/**
+ * Plays one game between two engines from start, each searching within budget
+ *
+ *  Parameters:
+ *      black   -   engine playing the Player's (1) discs
+ *      white   -   engine playing the CPU's (2) discs
+ *
+ * @returns:    the final position, or an error if an engine chose an illegal move
+ */
+pub fn play_game(black: &mut dyn Engine, white: &mut dyn Engine, start: &Board, budget: &MoveBudget) -> Result<Board, String> {
+    let mut board = start.clone();
+
+    while board.check_game_state(false) == 0 {
+        let (engine, val): (&mut dyn Engine, u8) = if board.is_player_turn() { (&mut *black, 1) } else { (&mut *white, 2) };
+
+        engine.set_position(&board);
+        match engine.choose_move(budget) {
+            Some(pos) if board.get_available_actions(false).contains(&pos) => board.ins(pos, val, false),
+            Some(pos) => return Err(format!("{} chose the illegal move {}", engine.name(), convert_num(pos))),
+            None => board.pass()
+        }
+    }
+
+    Ok(board)
+}
+
+/**
+ * Plays a match of games between two engines from start, swapping colours after every game
+ * so a and b move first equally often
+ */
+pub fn play_match(a: &mut dyn Engine, b: &mut dyn Engine, start: &Board, games: u32, budget: &MoveBudget) -> Result<MatchScore, String> {
+    let mut score = MatchScore::default();
+
+    for game in 0..games {
+        let a_black = game % 2 == 0;
+        let board = if a_black { play_game(a, b, start, budget)? } else { play_game(b, a, start, budget)? };
+
+        match (board.check_game_state(false), a_black) {
+            (3, _) => score.draws += 1,
+            (1, true) | (2, false) => score.wins += 1,
+            _ => score.losses += 1
+        }
+    }
+
+    Ok(score)
+}
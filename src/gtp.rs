@@ -0,0 +1,372 @@
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use reversi::clock::{MoveBudget, TimeManager};
+use reversi::{convert_num, parse_pos, Board, Engine};
+
+const ENGINE_NAME: &str = "ReversiMCTS";
+
+/**
+ * Search time per move when no time_settings have been given
+ */
+const DEFAULT_MOVE_SECS: u64 = 5;
+
+const COMMANDS: [&str; 15] = [
+    "protocol_version", "name", "version", "known_command", "list_commands", "quit",
+    "boardsize", "clear_board", "play", "genmove", "undo", "showboard", "final_score",
+    "time_settings", "time_left"
+];
+
+/**
+ * Game state of a GTP session
+ *
+ *      board       -   current position
+ *      history     -   positions before each move, for undo
+ *      clocks      -   game clocks of Black (Player, 1) and White (CPU, 2) once time_settings
+ *                      has been given
+ */
+struct Session {
+    board: Board,
+    history: Vec<Board>,
+    clocks: [Option<TimeManager>; 2],
+    byo_yomi: Option<MoveBudget>
+}
+
+/**
+ * Runs a Go Text Protocol style command loop over stdin/stdout until quit or end of input
+ *
+ * Commands follow GTP version 2: an optional numeric id, the command and its arguments,
+ * answered with `=[id] result` or `?[id] error` followed by an empty line. Colours are
+ * black/b (the Player, who moves first) and white/w (the CPU), vertices use the same
+ * notation as the game (A1 is the top left corner) and `pass` is only legal when the
+ * colour has no actions.
+ */
+pub fn run(engine: &mut dyn Engine) {
+    let stdin = io::stdin();
+    let mut session = Session {
+        board: Board::new(8, 8),
+        history: Vec::new(),
+        clocks: [None, None],
+        byo_yomi: None
+    };
+
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(_) => break
+        };
+
+        // Comments and empty lines are ignored
+        let line = line.split('#').next().unwrap_or("").trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut words: Vec<&str> = line.split_whitespace().collect();
+        let id: String = match words[0].parse::<u32>() {
+            Ok(n) => {
+                words.remove(0);
+                n.to_string()
+            },
+            Err(_) => String::new()
+        };
+
+        let command = words.first().cloned().unwrap_or("");
+        let args = if words.is_empty() { &[][..] } else { &words[1..] };
+
+        match execute(&mut session, engine, command, args) {
+            Ok(response) => print!("={} {}\n\n", id, response),
+            Err(error) => print!("?{} {}\n\n", id, error)
+        }
+        io::stdout().flush().unwrap();
+
+        if command == "quit" {
+            break;
+        }
+    }
+}
+
+fn execute(session: &mut Session, engine: &mut dyn Engine, command: &str, args: &[&str]) -> Result<String, String> {
+    match command {
+        "protocol_version" => Ok(String::from("2")),
+        "name" => Ok(String::from(ENGINE_NAME)),
+        "version" => Ok(String::from(env!("CARGO_PKG_VERSION"))),
+        "known_command" => Ok(COMMANDS.contains(&args.first().cloned().unwrap_or("")).to_string()),
+        "list_commands" => Ok(COMMANDS.join("\n")),
+        "quit" => Ok(String::new()),
+        "boardsize" => match args.first() {
+            Some(&"8") => Ok(String::new()),
+            _ => Err(String::from("unacceptable size"))
+        },
+        "clear_board" => {
+            session.board = Board::new(8, 8);
+            session.history.clear();
+            Ok(String::new())
+        },
+        "play" => {
+            let val = parse_color(args.first())?;
+            let vertex = args.get(1).ok_or("missing vertex")?;
+            play(session, val, vertex)?;
+            Ok(String::new())
+        },
+        "genmove" => {
+            let val = parse_color(args.first())?;
+            genmove(session, engine, val)
+        },
+        "undo" => match session.history.pop() {
+            Some(previous) => {
+                session.board = previous;
+                Ok(String::new())
+            },
+            None => Err(String::from("cannot undo"))
+        },
+        "showboard" => Ok(show_board(&session.board)),
+        "final_score" => {
+            let (black, white) = session.board.get_score();
+            Ok(if black > white {
+                format!("B+{}", black - white)
+            } else if white > black {
+                format!("W+{}", white - black)
+            } else {
+                String::from("0")
+            })
+        },
+        "time_settings" => {
+            let secs: Vec<u64> = args.iter().map(|a| a.parse::<u64>()).collect::<Result<_, _>>()
+                .map_err(|_| String::from("syntax error"))?;
+            match secs.as_slice() {
+                [main_time, byo_yomi_time, byo_yomi_stones] => {
+                    set_time(session, *main_time, *byo_yomi_time, *byo_yomi_stones);
+                    Ok(String::new())
+                },
+                _ => Err(String::from("syntax error"))
+            }
+        },
+        "time_left" => {
+            let val = parse_color(args.first())?;
+            let secs: u64 = args.get(1).and_then(|s| s.parse().ok()).ok_or("syntax error")?;
+            let index: usize = (val - 1).into();
+            if let Some(clock) = session.clocks[index].as_mut() {
+                clock.set_remaining(Duration::new(secs, 0));
+            }
+            Ok(String::new())
+        },
+        _ => Err(String::from("unknown command"))
+    }
+}
+
+/**
+ * Plays a vertex (or pass) for the colour val, which must be the side to move
+ */
+fn play(session: &mut Session, val: u8, vertex: &str) -> Result<(), String> {
+    let to_move: u8 = if session.board.is_player_turn() { 1 } else { 2 };
+    if val != to_move {
+        return Err(String::from("illegal move, not this colour's turn"));
+    }
+
+    let actions = session.board.get_available_actions(false);
+
+    if vertex.eq_ignore_ascii_case("pass") {
+        if !actions.is_empty() {
+            return Err(String::from("illegal move, pass with actions available"));
+        }
+        session.history.push(session.board.clone());
+        session.board.pass();
+        return Ok(());
+    }
+
+    match parse_pos(vertex) {
+        Some(pos) if actions.contains(&pos) => {
+            session.history.push(session.board.clone());
+            session.board.ins(pos, val, false);
+            Ok(())
+        },
+        _ => Err(String::from("illegal move"))
+    }
+}
+
+/**
+ * Searches for the colour val, plays the chosen move and returns its vertex
+ */
+fn genmove(session: &mut Session, engine: &mut dyn Engine, val: u8) -> Result<String, String> {
+    let to_move: u8 = if session.board.is_player_turn() { 1 } else { 2 };
+    if val != to_move {
+        return Err(String::from("not this colour's turn"));
+    }
+
+    if session.board.get_available_actions(false).is_empty() {
+        play(session, val, "pass")?;
+        return Ok(String::from("pass"));
+    }
+
+    let index: usize = (val - 1).into();
+    let (player_score, cpu_score) = session.board.get_score();
+    let budget = match (&session.clocks[index], &session.byo_yomi) {
+        (Some(clock), _) => clock.allocate(64 - player_score - cpu_score),
+        (None, Some(byo_yomi)) => *byo_yomi,
+        (None, None) => MoveBudget::fixed(DEFAULT_MOVE_SECS)
+    };
+
+    let start = Instant::now();
+    engine.set_position(&session.board);
+    let best = engine.choose_move(&budget);
+    if let Some(clock) = session.clocks[index].as_mut() {
+        clock.record(start.elapsed());
+    }
+
+    let vertex = best.map_or(String::from("pass"), convert_num);
+    play(session, val, &vertex)?;
+    Ok(vertex)
+}
+
+/**
+ * Applies time_settings to both colours
+ *
+ * Canadian byo-yomi is approximated as an increment of byo_yomi_time / byo_yomi_stones per
+ * move. Without main time each move gets the byo-yomi time per stone.
+ */
+fn set_time(session: &mut Session, main_time: u64, byo_yomi_time: u64, byo_yomi_stones: u64) {
+    let increment = if byo_yomi_stones > 0 {
+        Duration::from_secs_f64(byo_yomi_time as f64 / byo_yomi_stones as f64)
+    } else {
+        Duration::new(0, 0)
+    };
+
+    if main_time > 0 {
+        session.clocks = [
+            Some(TimeManager::new(Duration::new(main_time, 0), increment)),
+            Some(TimeManager::new(Duration::new(main_time, 0), increment))
+        ];
+        session.byo_yomi = None;
+    } else {
+        session.clocks = [None, None];
+        session.byo_yomi = if byo_yomi_stones > 0 {
+            Some(MoveBudget { target: increment, maximum: increment })
+        } else {
+            None
+        };
+    }
+}
+
+fn parse_color(arg: Option<&&str>) -> Result<u8, String> {
+    match arg.map(|a| a.to_ascii_lowercase()).as_deref() {
+        Some("b") | Some("black") => Ok(1),
+        Some("w") | Some("white") => Ok(2),
+        _ => Err(String::from("invalid color"))
+    }
+}
+
+/**
+ * Plain text board without colours: X is Black (Player), O is White (CPU)
+ */
+fn show_board(b: &Board) -> String {
+    let mut out = String::from("\n  A B C D E F G H\n");
+
+    for row in 0..8 {
+        out.push_str(&format!("{} ", row + 1));
+        for col in 0..8 {
+            let tile = match b.squares()[row * 8 + col] {
+                1 => "X",
+                2 => "O",
+                _ => "."
+            };
+            out.push_str(tile);
+            out.push(' ');
+        }
+        out.push_str(&format!("{}\n", row + 1));
+    }
+
+    let (black, white) = b.get_score();
+    let to_move = if b.is_player_turn() { "Black" } else { "White" };
+    out.push_str(&format!("  A B C D E F G H\nBlack (X): {}, White (O): {}, {} to move", black, white, to_move));
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use reversi::MctsEngine;
+
+    fn session() -> Session {
+        Session { board: Board::new(8, 8), history: Vec::new(), clocks: [None, None], byo_yomi: None }
+    }
+
+    fn run_command(session: &mut Session, line: &str) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        execute(session, &mut MctsEngine::easy(), words[0], &words[1..])
+    }
+
+    #[test]
+    fn vertices_and_colours() {
+        let mut s = session();
+
+        assert!(run_command(&mut s, "play b z9").is_err());
+        assert!(run_command(&mut s, "play b a1").is_err());
+        assert!(run_command(&mut s, "play red d3").is_err());
+        assert!(run_command(&mut s, "play w c4").is_err());
+        assert!(run_command(&mut s, "play b").is_err());
+
+        assert_eq!(run_command(&mut s, "play black D3"), Ok(String::new()));
+        assert_eq!(run_command(&mut s, "play W c3"), Ok(String::new()));
+        assert_eq!(s.history.len(), 2);
+    }
+
+    #[test]
+    fn play_and_undo() {
+        let mut s = session();
+        assert!(run_command(&mut s, "undo").is_err());
+
+        run_command(&mut s, "play b d3").unwrap();
+        let after = s.board.clone();
+        run_command(&mut s, "play w c3").unwrap();
+
+        run_command(&mut s, "undo").unwrap();
+        assert_eq!(s.board.squares(), after.squares());
+        assert!(!s.board.is_player_turn());
+
+        run_command(&mut s, "undo").unwrap();
+        assert_eq!(s.board.squares(), Board::new(8, 8).squares());
+        assert!(s.board.is_player_turn());
+    }
+
+    #[test]
+    fn pass_only_without_actions() {
+        let mut s = session();
+        assert!(run_command(&mut s, "play b pass").is_err());
+        assert!(s.history.is_empty());
+
+        // Black's only disc is next to White's in the corner, so only White has a move
+        s.board = Board::from_obf(&format!("OX{} X", "-".repeat(62))).unwrap();
+        assert_eq!(run_command(&mut s, "genmove b"), Ok(String::from("pass")));
+        assert!(!s.board.is_player_turn());
+
+        run_command(&mut s, "undo").unwrap();
+        assert_eq!(run_command(&mut s, "play b PASS"), Ok(String::new()));
+    }
+
+    #[test]
+    fn time_settings_and_time_left() {
+        let mut s = session();
+
+        run_command(&mut s, "time_settings 60 10 5").unwrap();
+        assert_eq!(s.clocks[0].as_ref().unwrap().remaining(), Duration::from_secs(60));
+        assert!(s.byo_yomi.is_none());
+
+        run_command(&mut s, "time_left white 25 0").unwrap();
+        assert_eq!(s.clocks[0].as_ref().unwrap().remaining(), Duration::from_secs(60));
+        assert_eq!(s.clocks[1].as_ref().unwrap().remaining(), Duration::from_secs(25));
+
+        // Without main time every move gets the byo-yomi time per stone
+        run_command(&mut s, "time_settings 0 10 5").unwrap();
+        assert!(s.clocks.iter().all(|c| c.is_none()));
+        assert_eq!(s.byo_yomi.map(|b| b.target), Some(Duration::from_secs(2)));
+
+        assert!(run_command(&mut s, "time_settings 60 10").is_err());
+        assert!(run_command(&mut s, "time_settings 60 ten 5").is_err());
+        assert!(run_command(&mut s, "time_left b").is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use reversi::clock::MoveBudget;
+use reversi::{convert_num, parse_pos, Board, Engine};
+
+/**
+ * Search time used when a request does not give time_ms, and the most a request may ask for
+ */
+const DEFAULT_TIME_MS: u64 = 1000;
+const MAX_TIME_MS: u64 = 60000;
+
+/**
+ * Largest request body accepted, every request body is a small JSON object
+ */
+const MAX_BODY_BYTES: u64 = 4096;
+
+/**
+ * Most games kept at once, creating another drops the game that was used longest ago
+ */
+const MAX_GAMES: usize = 1000;
+
+/**
+ * A game hosted by the server, with the moves played so far and when a request last used it
+ */
+struct Game {
+    board: Board,
+    moves: Vec<String>,
+    last_used: Instant
+}
+
+/**
+ * JSON view of a game
+ *
+ *      squares     -   the 64 squares row by row: 0 empty, 1 Player, 2 CPU
+ *      to_move     -   "player" or "cpu"
+ *      state       -   "in_progress", "player_won", "cpu_won" or "draw"
+ */
+#[derive(Serialize)]
+struct GameView {
+    id: u32,
+    squares: Vec<u8>,
+    to_move: &'static str,
+    score: ScoreView,
+    legal_moves: Vec<String>,
+    state: &'static str,
+    moves: Vec<String>
+}
+
+#[derive(Serialize)]
+struct ScoreView {
+    player: u8,
+    cpu: u8
+}
+
+#[derive(Serialize)]
+struct MoveAnalysis {
+    #[serde(rename = "move")]
+    action: String,
+    visits: u32,
+    win_rate: f32
+}
+
+#[derive(Deserialize)]
+struct MoveRequest {
+    #[serde(rename = "move")]
+    action: String
+}
+
+#[derive(Deserialize, Default)]
+struct SearchRequest {
+    time_ms: Option<u64>
+}
+
+/**
+ * Runs the HTTP JSON API on addr (e.g. 127.0.0.1:8080) until the process is stopped
+ *
+ * Endpoints:
+ *      POST /games                 -   creates a game, returns its GameView
+ *      GET  /games/{id}            -   returns the GameView
+ *      GET  /games/{id}/moves      -   returns the legal moves of the side to move
+ *      POST /games/{id}/moves      -   plays {"move": "d3"} for the side to move
+ *      POST /games/{id}/cpu        -   searches for {"time_ms": 1000} and plays the best move
+ *      POST /games/{id}/analysis   -   searches for {"time_ms": 1000} and returns every legal
+ *                                      move with its visits and win rate, best first
+ *
+ * Requests are handled one at a time, so a search blocks other requests until it finishes.
+ * Bodies over MAX_BODY_BYTES are refused, and once MAX_GAMES games exist creating another
+ * drops the game that was used longest ago.
+ */
+pub fn serve(addr: &str, engine: &mut dyn Engine) {
+    let server = match Server::http(addr) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("ERROR: could not start HTTP server on {}: {}", addr, e);
+            return;
+        }
+    };
+
+    println!("Serving the HTTP API on http://{}", addr);
+
+    let mut games: HashMap<u32, Game> = HashMap::new();
+    let mut next_id: u32 = 1;
+
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let (status, value) = match request.as_reader().take(MAX_BODY_BYTES + 1).read_to_string(&mut body) {
+            Ok(n) if n as u64 > MAX_BODY_BYTES => (413, json!({ "error": format!("request body is larger than {} bytes", MAX_BODY_BYTES) })),
+            Ok(_) => route(&mut games, &mut next_id, engine, request.method(), request.url(), &body),
+            Err(_) => (400, json!({ "error": "could not read request body" }))
+        };
+
+        respond(request, status, &value);
+    }
+}
+
+fn route(games: &mut HashMap<u32, Game>, next_id: &mut u32, engine: &mut dyn Engine, method: &Method, url: &str, body: &str) -> (u16, serde_json::Value) {
+    let path = url.split('?').next().unwrap_or("");
+    let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+
+    // CORS preflight for browser front-ends
+    if *method == Method::Options {
+        return (204, serde_json::Value::Null);
+    }
+
+    match (method, parts.as_slice()) {
+        (Method::Post, ["games"]) => {
+            if games.len() >= MAX_GAMES {
+                if let Some(oldest) = games.iter().min_by_key(|(_, g)| g.last_used).map(|(id, _)| *id) {
+                    games.remove(&oldest);
+                }
+            }
+
+            let id = *next_id;
+            *next_id += 1;
+            games.insert(id, Game { board: Board::new(8, 8), moves: Vec::new(), last_used: Instant::now() });
+            (201, view(id, &games[&id]))
+        },
+        (_, ["games", id, rest @ ..]) => {
+            let id: u32 = match id.parse() {
+                Ok(i) => i,
+                Err(_) => return (404, json!({ "error": "no such game" }))
+            };
+            let game = match games.get_mut(&id) {
+                Some(g) => g,
+                None => return (404, json!({ "error": "no such game" }))
+            };
+            game.last_used = Instant::now();
+
+            match (method, rest) {
+                (Method::Get, []) => (200, view(id, game)),
+                (Method::Get, ["moves"]) => (200, json!(legal_moves(&game.board))),
+                (Method::Post, ["moves"]) => match serde_json::from_str::<MoveRequest>(body) {
+                    Ok(req) => match play(game, &req.action) {
+                        Ok(()) => (200, view(id, game)),
+                        Err(e) => (400, json!({ "error": e }))
+                    },
+                    Err(e) => (400, json!({ "error": format!("invalid request: {}", e) }))
+                },
+                (Method::Post, ["cpu"]) => {
+                    if game.board.get_available_actions(false).is_empty() || game.board.check_game_state(false) != 0 {
+                        return (409, json!({ "error": "game is over" }));
+                    }
+                    let budget = match budget_from(body) {
+                        Ok(b) => b,
+                        Err(e) => return (400, json!({ "error": e }))
+                    };
+                    engine.set_position(&game.board);
+                    let best = match engine.choose_move(&budget) {
+                        Some(pos) => convert_num(pos),
+                        None => return (409, json!({ "error": "game is over" }))
+                    };
+                    match play(game, &best) {
+                        Ok(()) => (200, json!({ "move": best, "game": view(id, game) })),
+                        Err(e) => (500, json!({ "error": e }))
+                    }
+                },
+                (Method::Post, ["analysis"]) => {
+                    if game.board.get_available_actions(false).is_empty() || game.board.check_game_state(false) != 0 {
+                        return (409, json!({ "error": "game is over" }));
+                    }
+                    let budget = match budget_from(body) {
+                        Ok(b) => b,
+                        Err(e) => return (400, json!({ "error": e }))
+                    };
+                    engine.set_position(&game.board);
+                    engine.choose_move(&budget);
+                    let result = match engine.statistics() {
+                        Some(r) => r,
+                        None => return (500, json!({ "error": "the engine has no search statistics" }))
+                    };
+                    let moves: Vec<MoveAnalysis> = result.moves.iter().map(|m| MoveAnalysis {
+                        action: convert_num(m.action),
+                        visits: m.visits,
+                        win_rate: m.value
+                    }).collect();
+                    (200, json!({ "best": convert_num(result.best), "moves": moves }))
+                },
+                _ => (404, json!({ "error": "not found" }))
+            }
+        },
+        _ => (404, json!({ "error": "not found" }))
+    }
+}
+
+/**
+ * Plays a move for the side to move after checking it is legal
+ */
+fn play(game: &mut Game, action: &str) -> Result<(), String> {
+    if game.board.check_game_state(false) != 0 {
+        return Err(String::from("game is over"));
+    }
+
+    match parse_pos(action) {
+        Some(pos) if game.board.get_available_actions(false).contains(&pos) => {
+            let val: u8 = if game.board.is_player_turn() { 1 } else { 2 };
+            game.board.ins(pos, val, false);
+            game.moves.push(convert_num(pos));
+            Ok(())
+        },
+        _ => Err(format!("illegal move: {}", action))
+    }
+}
+
+/**
+ * Search budget from a request body, an empty body or one without time_ms uses the default
+ * time and longer times are cut to MAX_TIME_MS
+ */
+fn budget_from(body: &str) -> Result<MoveBudget, String> {
+    let req: SearchRequest = if body.trim().is_empty() {
+        SearchRequest::default()
+    } else {
+        serde_json::from_str(body).map_err(|e| format!("invalid request: {}", e))?
+    };
+
+    let time_ms = req.time_ms.unwrap_or(DEFAULT_TIME_MS);
+    if time_ms == 0 {
+        return Err(String::from("time_ms must be at least 1"));
+    }
+
+    let time = Duration::from_millis(time_ms.min(MAX_TIME_MS));
+    Ok(MoveBudget { target: time, maximum: time })
+}
+
+fn legal_moves(b: &Board) -> Vec<String> {
+    b.get_available_actions(false).iter().map(|a| convert_num(*a)).collect()
+}
+
+fn view(id: u32, game: &Game) -> serde_json::Value {
+    let (player, cpu) = game.board.get_score();
+
+    let state = match game.board.check_game_state(false) {
+        1 => "player_won",
+        2 => "cpu_won",
+        3 => "draw",
+        _ => "in_progress"
+    };
+
+    json!(GameView {
+        id,
+        squares: game.board.squares().to_vec(),
+        to_move: if game.board.is_player_turn() { "player" } else { "cpu" },
+        score: ScoreView { player, cpu },
+        legal_moves: legal_moves(&game.board),
+        state,
+        moves: game.moves.clone()
+    })
+}
+
+fn respond(request: Request, status: u16, value: &serde_json::Value) {
+    let body = if value.is_null() { String::new() } else { value.to_string() };
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap())
+        .with_header(Header::from_bytes(&b"Access-Control-Allow-Origin"[..], &b"*"[..]).unwrap())
+        .with_header(Header::from_bytes(&b"Access-Control-Allow-Methods"[..], &b"GET, POST, OPTIONS"[..]).unwrap())
+        .with_header(Header::from_bytes(&b"Access-Control-Allow-Headers"[..], &b"Content-Type"[..]).unwrap());
+
+    let _ = request.respond(response);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reversi::MctsEngine;
+
+    #[test]
+    fn search_budgets() {
+        assert_eq!(budget_from("").unwrap().target, Duration::from_millis(DEFAULT_TIME_MS));
+        assert_eq!(budget_from("{}").unwrap().target, Duration::from_millis(DEFAULT_TIME_MS));
+        assert_eq!(budget_from(r#"{"time_ms": 250}"#).unwrap().target, Duration::from_millis(250));
+        assert_eq!(budget_from(r#"{"time_ms": 999999}"#).unwrap().target, Duration::from_millis(MAX_TIME_MS));
+
+        for body in ["{", "null", r#"{"time_ms": "fast"}"#, r#"{"time_ms": -5}"#, r#"{"time_ms": 0}"#].iter() {
+            assert!(budget_from(body).is_err(), "{}", body);
+        }
+    }
+
+    #[test]
+    fn malformed_search_requests_are_refused() {
+        let mut games = HashMap::new();
+        let mut next_id = 1;
+        let mut engine = MctsEngine::easy();
+
+        route(&mut games, &mut next_id, &mut engine, &Method::Post, "/games", "");
+        let (status, value) = route(&mut games, &mut next_id, &mut engine, &Method::Post, "/games/1/cpu", r#"{"time_ms": "x"}"#);
+        assert_eq!(status, 400, "{}", value);
+        assert!(games[&1].moves.is_empty());
+    }
+
+    #[test]
+    fn least_recently_used_game_is_dropped() {
+        let mut games = HashMap::new();
+        let mut next_id = 1;
+        let mut engine = MctsEngine::easy();
+
+        for _ in 0..MAX_GAMES {
+            route(&mut games, &mut next_id, &mut engine, &Method::Post, "/games", "");
+        }
+        games.get_mut(&1).unwrap().last_used = Instant::now() + Duration::from_secs(1);
+
+        let (status, _) = route(&mut games, &mut next_id, &mut engine, &Method::Post, "/games", "");
+        assert_eq!(status, 201);
+        assert_eq!(games.len(), MAX_GAMES);
+        assert!(games.contains_key(&1));
+        assert!(!games.contains_key(&2));
+        assert!(games.contains_key(&(MAX_GAMES as u32 + 1)));
+    }
+}
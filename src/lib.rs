@@ -0,0 +1,57 @@
+/*!
+ * Reversi engine library
+ *
+ * Positions and move generation are in `board`, checked by `perft`, and game records in
+ * `record`. AIs implement the `Engine` trait in `engine`, which the built-in `MctsEngine` does
+ * on top of `search` (flat Monte Carlo and the `Searcher`), `tree` (PUCT tree search),
+ * `playout` (playout policies), `nn` (value/policy network) and `clock` (time management).
+ * Boards are printed with the colours and glyphs of the `theme` set with `theme::set_theme()`,
+ * rendered as images with `svg` and as animations with `animation`.
+ *
+ * Squares are numbered row by row from A1 in the top left corner (A1 = 0, H8 = 63) and hold
+ * 0 when empty, 1 for the Player (Black, moves first) and 2 for the CPU (White).
+ */
+
+/** Animated GIFs of games */
+pub mod animation;
+
+/** Board representation, move generation and square notation */
+pub mod board;
+
+/** Game clocks and per-move search budgets */
+pub mod clock;
+
+/** The Engine trait front-ends drive AIs through, and the built-in Monte Carlo engine */
+pub mod engine;
+
+/** Optional value/policy network for guiding playouts */
+pub mod nn;
+
+/** Leaf counts of the game tree for validating move generation */
+pub mod perft;
+
+/** Pluggable policies for choosing moves during playouts */
+pub mod playout;
+
+/** Game records that can be saved, loaded and replayed */
+pub mod record;
+
+/** Flat Monte Carlo search and the Searcher front-end shared by every mode */
+pub mod search;
+
+/** Rendering of positions and games as SVG images */
+pub mod svg;
+
+/** Colour themes and glyphs used to print boards */
+pub mod theme;
+
+/** Tree search with PUCT selection */
+pub mod tree;
+
+pub use board::{convert_2d, convert_num, flipped, get_new_pos, parse_pos, Board};
+pub use engine::{Engine, MctsEngine, StopHandle};
+pub use record::GameRecord;
+pub use search::{
+    get_max_tile, monte_carlo_tree_search, random_playout, MoveStats, SearchProgress, SearchResult,
+    Searcher, MAX_SIMULATIONS, MAX_STEPS, WEIGHTS
+};
@@ -1,895 +1,509 @@
+use std::fs;
 use std::io;
-use std::io::stdout;
-use std::io::Write; 
-use rand::Rng;
+use std::path::Path;
 use regex::Regex;
 
-// HashMap is used in order to assign a count to each element inside win/draw/loss stats
-use std::collections::HashMap;
-
 // IndexSet provides an indexed HashSet to allow returning element by index
-// Used for getting random items from set in O(1) time so MCTS is more efficient
-// Docs: https://docs.rs/indexmap/1.5.0/indexmap/set/struct.IndexSet.html
 use indexmap::IndexSet;
 
 // Used to limit MCTS duration
 use std::time::{Duration, Instant};
 
-// Pretty board styling
-use ansi_term::Color::{Red, Green};
-use ansi_term::Style;
-
-/** 
- * Game Board Struct
- * 
- * Manages the board vector and the information about it, including...
- *      - perimeter tiles
- *      - whether it is the players turn
- *      - available actions for both player and cpu
- * 
- * Board.board elements are u8 integers, which represent:
- *      0 => Empty Square
- *      1 => Player
- *      2 => CPU
-*/
-struct Board {
-    width: u8,
-    height: u8,
-    board_size: u8,
-    board: Vec<u8>,
-    perimeter: IndexSet<u8>,
-    player_available_actions: IndexSet<u8>,
-    cpu_available_actions: IndexSet<u8>,
-    player_turn: bool
-}
-
-/**
- * Board object functions
- */
-impl Board {
-
-    /**
-     * Initializes a Reversi game board
-     * 
-     */
-    fn new(w: u8, h: u8) -> Board {
-
-        let size = w * h;
-        let mut player_actions: IndexSet<u8> = IndexSet::new();
-        let mut cpu_actions: IndexSet<u8> = IndexSet::new();
-        let mut perimeter_tiles: IndexSet<u8> = IndexSet::new();
-        let mut new_board = vec![0; (size).into()];
-        
-        new_board[28] = 1;
-        new_board[35] = 1;
-        new_board[27] = 2;
-        new_board[36] = 2;
-
-        player_actions.insert(26);
-        player_actions.insert(19);
-        player_actions.insert(37);
-        player_actions.insert(44);
-
-        cpu_actions.insert(29);
-        cpu_actions.insert(20);
-        cpu_actions.insert(34);
-        cpu_actions.insert(43);
-
-        perimeter_tiles.insert(18);
-        perimeter_tiles.insert(19);
-        perimeter_tiles.insert(20);
-        perimeter_tiles.insert(21);
-        perimeter_tiles.insert(26);
-        perimeter_tiles.insert(29);
-        perimeter_tiles.insert(34);
-        perimeter_tiles.insert(37);
-        perimeter_tiles.insert(42);
-        perimeter_tiles.insert(43);
-        perimeter_tiles.insert(44);
-        perimeter_tiles.insert(45);
-
-        Board {
-            width: w,
-            height: h,
-            board_size: size,
-            board: new_board, //must convert u8 type -> usize type
-            perimeter: perimeter_tiles,
-            player_available_actions: player_actions,
-            cpu_available_actions: cpu_actions,
-            player_turn: true // Player always takes the first turn
-        }
-    }
-
-    /**
-     * 
-     */
-    fn clone(&self) -> Board {
-      
-        let new_board: Board = Board {
-            width: self.width,
-            height: self.height,
-            board_size: self.board_size,
-            board: self.board.clone(),
-            perimeter: self.perimeter.clone(),
-            player_available_actions: self.player_available_actions.clone(),
-            cpu_available_actions: self.cpu_available_actions.clone(),
-            player_turn: self.player_turn // Player always takes the first turn
-        };
-
-        new_board
-    }
-
-    /**
-     * Print the board vec to the screen
-     * 
-     * Players tiles are printed in RED
-     * CPUs tiles are printed in GREEN
-     */
-    fn print(&self, debug: bool) {
-
-        let (player_score, cpu_score): (u8, u8) = self.get_score();
-
-        println!("\n     {}", Style::default().bold().paint("A B C D E F G H") );
-
-        let mut count = 0;
-        for i in self.board.iter() {
-            if count % self.width == 0 {
-                if count != 0 {
-                    let row_num: u8 = count / 8;
-                    print!("{}\n     ", Style::default().bold().paint(row_num.to_string()));
-                }else{  
-                    print!("     ")
-                }
-            }
-            if i == &1 {
-                print!("{} ", Red.paint("●"));
-            } else if i == &2 {
-                print!("{} ", Green.paint("●"));
-            } else {
-                if self.player_available_actions.contains(&count) {
-                    print!("{} ", Style::default().bold().paint("*"));
-                } else { 
-                    print!("- "); 
-                }
-            }
-            count += 1; 
-        }
-        print!("{}\n\n", Style::default().bold().paint("8"));
-
-        println!("     Player: {}, CPU: {}\n", Red.paint(player_score.to_string()), Green.paint(cpu_score.to_string()));
-
-    }
+// Shares the spectator stream between the game loop and the engine's progress callback
+use std::rc::Rc;
 
-    /**
-     * Handles a piece being put onto the board
-     * 
-     * Adds to board -> flips pieces -> update perimeter -> updates available actions -> change turns
-     */
-    fn ins(&mut self, pos: u8, val: u8, debug: bool) {
+// The engine: positions, move generation, game records and searches
+use reversi::animation::{save_game_gif, GifOptions};
+use reversi::clock::{MoveBudget, TimeManager};
+use reversi::engine::{play_match, ProgressCallback};
+use reversi::nn::Network;
+use reversi::perft::{divide, perft};
+use reversi::playout::policy_from_name;
+use reversi::svg::{position_svg, save_game_svgs, SvgOptions};
+use reversi::theme;
+use reversi::theme::Theme;
+use reversi::tree::{PuctConfig, Rave};
+use reversi::{convert_2d, convert_num, Board, Engine, GameRecord, MctsEngine, SearchProgress, SearchResult};
 
-        // Add new tile to board
-        let pos_u: usize = match self.get_available_actions(debug).contains(&pos) {
-            false => {
-                println!("ERROR: {} is not a valid action", pos);
-                return;
-            },
-            true => pos.into()
-        };
+// NBoard engine protocol front-end
+mod nboard;
 
-        self.board.splice(pos_u..pos_u+1, [val].iter().cloned());
+// Go Text Protocol style front-end for scripting
+mod gtp;
 
-        let mut u: u8 = 1;
-        let mut tiles = Vec::new();
+// Two player games over TCP
+mod net;
 
-        // Manages the direction of iteration
-        for direction in 0..8 {
+// HTTP JSON API for web front-ends
+mod http;
 
-            // This part of the function iterates in all 8 directions from the tile, checking if any of
-            // the tiles in these directions will be flipped -> that is, they are...
-            //                      - adjacent to the newly placed tile, or
-            //                      - in a span of opposing tiles adjacent to the newly placed tile, and
-            //                      - has a tile on the other side of the opposing tiles that "sandwiches"
-            //                          them with no empty spaces inbetween
+// WebSocket streaming of games to spectators
+mod stream;
+use stream::Broadcaster;
 
-            u = 1;
-            tiles.clear();
+// Full-screen terminal UI
+mod tui;
 
-            loop {
+// Stepping through saved games
+mod replay;
 
-                // Depending on direction, changes the formula for iteration
-                let new_pos: u8 = match get_new_pos(direction, pos, u, self.board_size) {
-                    None => break,
-                    Some(x) => Some(x).unwrap()
-                };
+// Setting up positions to play from
+mod editor;
 
-                let new_pos_usize: usize = new_pos.into();
+fn print_title() {
+    println!("################################################################");
+    println!("#                                                              #");
+    println!("#                {}                #", theme::current().bold("Welcome to Reversi against AI!"));
+    println!("#                                                              #");
+    println!("################################################################\n\n");
+}
 
-                let tile = self.board.get(new_pos_usize).unwrap();
+fn print_help() {
+    let theme: Theme = theme::current();
 
-                // Refer to comment above for explanation
-                if tile != &val && tile != &0 {
-                    tiles.push(new_pos);
-                } else if tile == &val {
-                    for t in &tiles {
-                        self.add(*t, val);
-                    }
-                } else {
-                    tiles.clear();
-                    break;
-                }
-                
-                u += 1;
-            }
-        }
+    println!("\nCommands:\n");
+    println!("  {}  -  print the current available actions", theme.bold("actions"));
+    println!("  {}  -  search and print every available action's statistics, best first", theme.bold("analyze"));
+    println!("                 ('analyze board' also ranks them on the board)");
+    println!("  {}     -  suggest a move for you", theme.bold("hint"));
+    println!("  {}       -  show the CPU's principal variation and search statistics for its last move", theme.bold("pv"));
+    println!("  {}  -  show game rules", theme.bold("rules"));
+    println!("  {}  -  save the position as an SVG image, e.g. 'svg position.svg'", theme.bold("svg <file>"));
+    println!("  {} -  save the game so far, to be replayed with --replay <file>", theme.bold("save <file>"));
+    println!("  {} -  count the positions up to n moves ahead (n up to 8), checking move generation", theme.bold("perft <n>"));
+    println!("  {}    -  toggles showing debug information", theme.bold("debug"));
+    println!("  {}     -  quit the game", theme.bold("exit"));
+    println!();
+}
 
-        // Remove inserted tile from perimeter
-        self.perimeter.remove(&pos);
+fn print_actions(actions: IndexSet<u8>) {
+    print!("\nPlayer's Actions: ");
+    for action in actions {
+        print!("{} ", theme::current().bold(&convert_num(action)));
+    }
+    println!("\n");
+}
 
-        // Adds the specified spaces to perimeter IndexSet
-        // Update perimeter above
-        for i in 0..3 {
-            let new_pos: u8 = match pos.checked_sub(9 - i) {
-                None => continue,
-                Some(x) => Some(x).unwrap()
-            };
-            let new_pos_usize: usize = new_pos.into();
-            if self.board.get(new_pos_usize).unwrap() == &0 { // implement row overflow handling
-                self.perimeter.insert(new_pos);
-            }
-        }
-        
-        // Update perimeter to the left
-        match pos.checked_sub(1) {
-            Some(x) => {
-                let new_pos = Some(x).unwrap();
-                let new_pos_usize: usize = Some(x).unwrap().into();
-                if self.board.get(new_pos_usize).unwrap() == &0 {
-                    self.perimeter.insert(new_pos);
-                }
-            },
-            None => {
-                if debug {
-                    println!("Overflow, but it's chill, I handled it")
-                }
-            }
-        };
+/**
+ * Prints each analysed action with its visits, win/draw/loss rates and expected disc
+ * differential for the side to move, in the order of the search result (best first)
+ *
+ * If annotate is set the board is printed with each action's rank on its square.
+ */
+fn print_analysis(board: &Board, result: &SearchResult, annotate: bool) {
+    let rank_label = |rank: usize| std::char::from_digit(rank as u32 + 1, 36).unwrap_or('?');
 
-        // Update perimeter to the right
-        match pos + 1 < self.board_size {
-            true => {
-                let new_pos = pos + 1;
-                let new_pos_usize: usize = new_pos.into();
-                if self.board.get(new_pos_usize).unwrap() == &0 {
-                    self.perimeter.insert(new_pos);
-                }
-            },
-            false => {
-               if debug {
-                   println!("Overflow, but it's chill, I handled it")
-               }
-            }
-        }
-        
-        // Update perimeter below
-        for i in 0..3 {
-            let new_pos: u8 = pos + 9 - i;
-            let new_pos_usize: usize = new_pos.into();
-            if new_pos < self.board_size {
-                if self.board.get(new_pos_usize).unwrap() == &0 {
-                    self.perimeter.insert(new_pos);
-                }
-            }
-        }
+    if annotate {
+        let labels: Vec<(u8, char)> = result.moves.iter().enumerate().map(|(rank, m)| (m.action, rank_label(rank))).collect();
+        board.print_annotated(&labels);
+    }
 
-        if debug { println!("{:?}", self.perimeter); }
+    println!("\n      {}", theme::current().bold("Move  Visits    Win   Draw   Loss   Discs"));
+    for (rank, m) in result.moves.iter().enumerate() {
+        let finished = m.finished().max(1) as f32;
+        println!("   {}  {:>4}  {:>6}  {:>5.1}% {:>5.1}% {:>5.1}%  {:>+6.1}",
+            rank_label(rank), convert_num(m.action), m.visits,
+            100.0 * m.wins as f32 / finished, 100.0 * m.draws as f32 / finished, 100.0 * m.losses as f32 / finished,
+            m.disc_diff);
+    }
+    println!();
+}
 
-        // Update available actions
-        self.player_available_actions.remove(&pos);
-        self.cpu_available_actions.remove(&pos);
+/**
+ * Describes a search: its principal variation, the best move's estimated score and the
+ * number of playouts searched
+ */
+fn search_summary(result: &SearchResult) -> String {
+    let pv: Vec<String> = result.pv.iter().map(|pos| convert_num(*pos)).collect();
+    let (value, disc_diff) = result.best_stats().map_or((0.5, 0.0), |m| (m.value, m.disc_diff));
 
-        // For each player 1 and 2...
-        for player in 1..3 {
-            // For each tile in the perimeter
-            for tile in self.get_perimeter() {
-                // Check if that tile is an available action
-                self.check_tile_actions(tile, player, debug);
-            }
-        }
+    format!("Principal variation: {}\nScore: {:.1}% ({:+.1} discs), {} playouts in {:.1}s ({:.0} per second)",
+        pv.join(" "), value * 100.0, disc_diff, result.playouts, result.elapsed.as_secs_f64(), result.playouts_per_second())
+}
 
-        // Alternate turns
-        if self.player_turn {
-            if debug { println!("Player's turn"); }
-            self.player_turn = false
-        } else {
-            if debug { println!("CPU's turn"); }
-            self.player_turn = true
+/**
+ * Writes a frame for every position of the game saved at path, e.g. game.txt to game-000.svg,
+ * game-001.svg, ...
+ */
+fn export_svg(path: &str) {
+    let record = match GameRecord::load(path) {
+        Ok(r) => r,
+        Err(e) => {
+            println!("ERROR: could not load {}: {}", path, e);
+            return;
         }
+    };
 
-        if debug {
-            println!("Player's Available Actions: {:?}", self.get_player_actions());
-            println!("CPU's Available Actions: {:?}", self.get_cpu_actions());
-        }
+    let prefix = Path::new(path).with_extension("");
+    match save_game_svgs(&record, &prefix.to_string_lossy(), &SvgOptions::default()) {
+        Ok(paths) => println!("Wrote {} frames, {} to {}", paths.len(), paths[0], paths[paths.len() - 1]),
+        Err(e) => println!("ERROR: could not write the frames: {}", e)
     }
+}
 
-    /**
-     * Given a tile position it will check in all directions if it is an available option 
-     * for player with the input val (1 or 2)
-     */
-    fn check_tile_actions(&mut self, pos: u8, val: u8, debug: bool){
-
-        let mut u: u8 = 1; // used as the iter for get_new_pos()
-        let mut tiles = Vec::new();
-
-        // Manages the direction of iteration
-        for direction in 0..8 {
-
-            u = 1;
-            tiles.clear();
-
-            loop {
-
-                // Depending on direction, changes the formula for iteration
-                let new_pos: u8 = match get_new_pos(direction, pos, u, self.board_size) {
-                    None => break,
-                    Some(x) => Some(x).unwrap()
-                };
-
-                let new_pos_usize: usize = new_pos.into();
-                let tile = self.board.get(new_pos_usize).unwrap(); // Gets value from tile at new position
-
-                if tile != &val && tile != &0 {
-                    // If the tile is not the same color as inserted, add to tiles vec
-                    tiles.push(new_pos);
-                } else if tile == &val && tiles.len() != 0 {
-                    // If there is a tile the same color as the initial val with opposing tiles inbetween...
-                    if val == 1 {
-                        if debug {
-                            println!("Added {} to actions for Player {}", new_pos, val);
-                        }
-                        self.player_available_actions.insert(pos);
-                        tiles.clear();
-                        return;
-                    } else {
-                        if debug {
-                            println!("Added {} to actions for CPU {}", new_pos, val);
-                        }
-                        self.cpu_available_actions.insert(pos);
-                        tiles.clear();
-                        return;
-                    }
-                } else {
-                    // Else, blank tile means not available action 
-                    if debug {
-                        println!("Removed {} from actions for player {}", pos, val);
-                    }
-                    if val == 1 {
-                        self.player_available_actions.remove(&pos);
-                    } else {
-                        self.cpu_available_actions.remove(&pos);
-                    }
-
-                    tiles.clear();
-                    break;
-                }
-                u += 1;
-
-            }
+/**
+ * Writes the game saved at path as an animated GIF, e.g. game.txt to game.gif
+ */
+fn export_gif(path: &str) {
+    let record = match GameRecord::load(path) {
+        Ok(r) => r,
+        Err(e) => {
+            println!("ERROR: could not load {}: {}", path, e);
+            return;
         }
-    }
+    };
 
-    /**
-     * Returns a clone of the IndexSet of available actions depending on which players turn it is
-     * 
-     * Should only use this function to get the available actions, don't individually
-     * reference the player or cpu sets
-     */
-    fn get_available_actions(&self, debug: bool) -> IndexSet<u8> {
-        if self.player_turn {
-            let actions = self.get_player_actions();
-            if debug {
-                println!("Player Available Actions: {:?}", actions);
-            }  
-            actions
-        } else {
-            let actions = self.get_cpu_actions();
-            if debug {
-                println!("CPU Available Actions: {:?}", actions);
-            }
-            actions
-        }
+    let gif_path = Path::new(path).with_extension("gif");
+    match save_game_gif(&record, &gif_path.to_string_lossy(), &GifOptions::default()) {
+        Ok(()) => println!("Wrote {} moves to {}", record.moves().len(), gif_path.display()),
+        Err(e) => println!("ERROR: could not write {}: {}", gif_path.display(), e)
     }
+}
 
-    fn get_player_actions(&self) -> IndexSet<u8> {
-        IndexSet::clone(&self.player_available_actions)
+/**
+ * Prints perft() from board for every depth up to depth with the time it took, and with divide
+ * also the leaves below each move at the last depth, which are summed for its count rather
+ * than counted twice
+ */
+fn print_perft(board: &Board, depth: u32, divide_moves: bool) {
+    let mut split: Vec<(Option<u8>, u64)> = Vec::new();
+
+    for d in 1..=depth {
+        let start_time = Instant::now();
+        let leaves = if divide_moves && d == depth {
+            split = divide(board, d);
+            if split.is_empty() { perft(board, d) } else { split.iter().map(|(_, n)| n).sum() }
+        } else {
+            perft(board, d)
+        };
+        let secs = start_time.elapsed().as_secs_f64();
+        println!("perft {:>2}: {:>12} leaves in {:.3}s ({:.0} per second)", d, leaves, secs, leaves as f64 / secs.max(1e-9));
     }
 
-    fn get_cpu_actions(&self) -> IndexSet<u8> {
-        IndexSet::clone(&self.cpu_available_actions)
+    if divide_moves {
+        println!();
+        for (mv, leaves) in split {
+            println!("  {}: {}", mv.map_or(String::from("PA"), convert_num), leaves);
+        }
     }
+}
 
-    fn is_player_turn(&self) -> bool {
-        self.player_turn
+/**
+ * Built-in engine by name, for matches between engines
+ */
+fn engine_from_name(name: &str) -> Result<MctsEngine, String> {
+    match name {
+        "easy" => Ok(MctsEngine::easy()),
+        "hard" => Ok(MctsEngine::hard()),
+        "puct" => Ok(MctsEngine::easy().with_puct(PuctConfig::default())),
+        _ => Err(format!("unknown engine '{}', expected easy, hard or puct", name))
     }
+}
 
-    /**
-     * Returns IndexSet of the tiles in the perimeter of the board pieces
-     */
-    fn get_perimeter(&self) -> IndexSet<u8> {
-        IndexSet::clone(&self.perimeter)
+/**
+ * Plays a match between the two engines named in engines (e.g. "easy,hard") from board and
+ * prints the score of the first, with --games and --move-time
+ */
+fn run_match(args: &[String], engines: &str, board: &Board) -> Result<(), String> {
+    let (a_name, b_name) = engines.split_once(',').ok_or_else(|| format!("expected two engines such as easy,hard, got {}", engines))?;
+    let mut a = engine_from_name(a_name)?;
+    let mut b = engine_from_name(b_name)?;
+
+    let games: u32 = arg_parse(args, "--games", 2)?;
+    let secs: f64 = arg_parse(args, "--move-time", 1.0)?;
+    if !(secs > 0.0 && secs.is_finite()) {
+        return Err(String::from("--move-time must be greater than 0"));
     }
+    let time = Duration::from_secs_f64(secs);
 
-    // Returns: 
-    // 0 -> incomplete
-    // 1 -> player win
-    // 2 -> cpu win
-    // 3 -> draw   
-    fn check_game_state(&self, debug: bool) -> u8 {
-        let player_actions = self.get_player_actions();
-        let cpu_actions = self.get_cpu_actions();
+    println!("{} vs {}, {} games at {}s per move...", a.name(), b.name(), games, secs);
+    let score = play_match(&mut a, &mut b, board, games, &MoveBudget { target: time, maximum: time })?;
+    println!("{}: {} wins, {} draws, {} losses", a_name, score.wins, score.draws, score.losses);
 
-        // GAME IS ENDED
-        if cpu_actions.len() == 0 || player_actions.len() == 0 {         
-
-            let (player_score, cpu_score): (u8, u8) = self.get_score();
+    Ok(())
+}
 
-            if debug {
-                println!("  Player: {}, CPU: {}", Red.paint(player_score.to_string()), Green.paint(cpu_score.to_string()));
-            }
-            
-            if player_score > cpu_score {
-                return 1;
-            } else if cpu_score > player_score {
-                return 2;
-            } else {
-                return 3;
-            }
-        }
+fn print_rules() {
+    let theme: Theme = theme::current();
 
-        else { 0 }
+    println!("      #                {}                #\n", theme.bold("REVERSI RULES"));
+    println!(" * {} tiles represent the user's spots, {} represent the CPUs.\n", theme.paint_player(theme.player_name), theme.paint_cpu(theme.cpu_name));
+    println!(" * The user starts by placing a tile adjacent to one of the CPU's tiles.\n Possible actions are marked by {} on the board.\n", theme.action);
+    println!(" * The game ends when either player cannot play a piece or the\n board is full.  The player with the most tiles wins.\n");
+}
 
+fn toggle_debug(debug: bool) -> bool {
+    if debug {
+        println!("Debug turned OFF");
+        false
+    } else {
+        println!("Debug turned ON");
+        true
     }
+}
 
-    /**
-     * get_score() -> returns tuple containing current score for player and cpu
-     */
-    fn get_score(&self) -> (u8, u8) {
-        let mut count_player = 0;
-        let mut count_cpu = 0;
-
-        for i in 0..64 {
-            match self.board.get(i).unwrap() {
-                0 => continue,
-                1 => count_player += 1,
-                2 => count_cpu += 1,
-                _ => println!("Error Code: ID10T" )
-            }
-        }
-
-        (count_player, count_cpu)
-    }
+/**
+ * Returns the value following a command line flag, e.g. `--network weights.txt`
+ */
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).cloned()
+}
 
-    /**
-     * Add value at position on board
-     * 
-     * val = 0: unused square
-     * val = 1: player piece
-     * val = 2: cpu piece
-     */
-    fn add(&mut self, pos: u8, val: u8) {
-        let pos_u: usize = pos.into();
-        self.board.splice(pos_u..(pos_u + 1), [val].iter().cloned());
+/**
+ * Parses the value following a command line flag, or returns the default if the flag is missing
+ */
+fn arg_parse<T: std::str::FromStr>(args: &[String], flag: &str, default: T) -> Result<T, String> {
+    match arg_value(args, flag) {
+        Some(v) => v.parse().map_err(|_| format!("invalid value for {}: {}", flag, v)),
+        None => Ok(default)
     }
 }
 
-/** 
- * Returns a new position based on direction, initial pos, iteration, and board size
- * Intended to be used in a loop (such as in the Board.ins() function)
- * 
- * @returns: Some(x) if new position is on board, or
- * @returns: None if position overflows board
+/**
+ * Builds the PUCT settings from the --cpuct, --noise, --dirichlet-alpha, --temperature,
+ * --rave and --rave-bias flags
  */
-fn get_new_pos(dir: u8, pos: u8, iter: u8, size: u8) -> Option<u8> {
-    let new_pos: Option<u8> = match dir {
-
-        0 => { // Right
-            let position = pos + iter;
-            if position % 8 == 0 {
-                None
-            } else {
-                Some(position)
-            }
-        },
-
-        1 => { // Left
-            let position = match pos.checked_sub(iter) {
-                None => None,
-                Some(x) => {
-                    if Some(x).unwrap() % 8 == 7 {
-                        None
-                    } else {
-                        Some(x)
-                    }
-                }
-            };
-            position
-        },
-
-        2 => { // Down
-            let position = pos + (iter * 8);
-            if position < size {
-                Some(position)
-            } else {
-                None
-            }
-        },
-
-        3 => { // Up
-            let new_pos = match pos.checked_sub(iter * 8) {
-                None => None,
-                Some(x) => Some(x)
-            };
-            new_pos
-        },
-
-        4 => { // Up left: must check that doesn't % 8 = 7 and doesn't overflow
-            let new_pos = match pos.checked_sub(iter * 8 + iter) {
-                None => None,
-                Some(x) => {
-                    if Some(x).unwrap() % 8 != 7 {
-                        Some(x)
-                    } else {
-                        None
-                    }
-                }
-            }; 
-            new_pos
-        },
-
-        5 => { // Up right: must check that doesn't % 8 = 0 and doesn't overflow
-            let new_pos = match pos.checked_sub(iter * 8 - iter) {
-                None => None,
-                Some(x) => {
-                    if Some(x).unwrap() % 8 != 0 {
-                        Some(x)
-                    } else {
-                        None
-                    }
-                }
-            };
-            new_pos
-            
-        },
-
-        6 => { // Down left: must check that doesnt % 8 = 7 and 
-            let position = pos + (iter * 8) - iter;
-            if position < size && position % 8 != 7 {
-                Some(position)
-            } else {
-                None
-            }
+fn puct_config_from_args(args: &[String]) -> Result<PuctConfig, String> {
+    let default = PuctConfig::default();
+
+    // --rave <k> uses the equivalence schedule, --rave-bias <b> the minimum MSE schedule
+    let rave = match (arg_value(args, "--rave"), arg_value(args, "--rave-bias")) {
+        (_, Some(_)) => Some(Rave::MinMse(arg_parse(args, "--rave-bias", 0.0)?)),
+        (Some(_), None) => Some(Rave::Equivalence(arg_parse(args, "--rave", 0.0)?)),
+        (None, None) => None
+    };
+    match rave {
+        Some(Rave::Equivalence(k)) if !(k > 0.0 && k.is_finite()) => {
+            return Err(format!("--rave must be greater than 0, got {}", k));
         },
-
-        7 => { // Down left: must check that doesnt % 8 = 7 and 
-            let position = pos + (iter * 8) + iter;
-            if position < size && position % 8 != 0 {
-                Some(position)
-            } else {
-                None
-            }
+        Some(Rave::MinMse(bias)) if !(bias >= 0.0 && bias.is_finite()) => {
+            return Err(format!("--rave-bias must not be negative, got {}", bias));
         },
+        _ => ()
+    }
 
-        _ => None
+    let config = PuctConfig {
+        c_puct: arg_parse(args, "--cpuct", default.c_puct)?,
+        dirichlet_alpha: arg_parse(args, "--dirichlet-alpha", default.dirichlet_alpha)?,
+        dirichlet_epsilon: arg_parse(args, "--noise", default.dirichlet_epsilon)?,
+        temperature: arg_parse(args, "--temperature", default.temperature)?,
+        rave
     };
 
-    new_pos
+    // Written so that NaN fails every check
+    if !(config.c_puct > 0.0 && config.c_puct.is_finite()) {
+        return Err(format!("--cpuct must be greater than 0, got {}", config.c_puct));
+    }
+    if !(0.0..=1.0).contains(&config.dirichlet_epsilon) {
+        return Err(format!("--noise must be from 0 to 1, got {}", config.dirichlet_epsilon));
+    }
+    if !(config.dirichlet_alpha > 0.0 && config.dirichlet_alpha.is_finite()) {
+        return Err(format!("--dirichlet-alpha must be greater than 0, got {}", config.dirichlet_alpha));
+    }
+    // Leaving --temperature out plays the most visited move
+    if arg_value(args, "--temperature").is_some() && !(config.temperature > 0.0 && config.temperature.is_finite()) {
+        return Err(format!("--temperature must be greater than 0, got {}", config.temperature));
+    }
+
+    Ok(config)
 }
 
 /**
- * Convert 2d string index to vector index
- * @params:     s: &str - len 2 string of char A-H followed by int 1-8
- * @returns:    u8 position in 1d Vec
+ * Builds the CPU's game clock from the --time and --increment flags (in seconds)
  */
-fn convert_2d(s: &str) -> u8{
-
-    //Handle panic
-    let letter = s.chars().next().unwrap().to_ascii_lowercase();
-    let num = s.chars().nth(1).unwrap();
-
-    let col: u8 = match letter {
-        'a' => 0,
-        'b' => 1,
-        'c' => 2,
-        'd' => 3,
-        'e' => 4,
-        'f' => 5,
-        'g' => 6,
-        'h' => 7,
-        _   => 42
-    };
+fn clock_from_args(args: &[String]) -> Result<Option<TimeManager>, String> {
+    let total: f64 = arg_parse(args, "--time", 0.0)?;
+    let increment: f64 = arg_parse(args, "--increment", 0.0)?;
 
-    // Probably better way to do this.... but I couldn't find it
-    let row: u8 = match num {
-        '1' => 0,
-        '2' => 1,
-        '3' => 2,
-        '4' => 3,
-        '5' => 4,
-        '6' => 5,
-        '7' => 6,
-        '8' => 7,
-        _   => 42
-    };
+    if total < 0.0 || increment < 0.0 {
+        return Err(String::from("--time and --increment must not be negative"));
+    }
+
+    if total == 0.0 {
+        return Ok(None);
+    }
 
-    row * 8 + col
+    Ok(Some(TimeManager::new(Duration::from_secs_f64(total), Duration::from_secs_f64(increment))))
 }
 
 /**
- * Convert integer vector index into 2d string index
- * Note: this function is the inverse of convert_2d()
- * @params:     num: less than 64 valued integer representing 1d index of vector
- * @returns:    String of values [a-h][1-8]
+ * Reads the board theme from --theme and --ascii, dropping colours if stdout can't show them
  */
-fn convert_num(num: u8) -> String {
-
-    let val: f64 = (num / 8).into();
-
-    let letter: &str = match val.floor() as u8 {
-        0 => "A",
-        1 => "B",
-        2 => "C",
-        3 => "D",
-        4 => "E",
-        5 => "F",
-        6 => "G",
-        7 => "H",
-        _ => {
-            println!("ERROR convert_num() -> input too large");
-            "ERR"
-        }
+fn theme_from_args(args: &[String]) -> Result<Theme, String> {
+    let theme = match arg_value(args, "--theme") {
+        Some(name) => Theme::from_name(&name)?,
+        None if args.iter().any(|a| a == "--ascii") => Theme::ASCII,
+        None => Theme::RED_GREEN
     };
 
-    format!("{}{}", letter, num % 8 + 1)
-}
-
-fn print_title() {
-    println!("################################################################");
-    println!("#                                                              #");
-    println!("#                {}                #", Style::default().bold().paint("Welcome to Reversi against AI!"));
-    println!("#                                                              #");
-    println!("################################################################\n\n");
+    Ok(theme.for_stdout())
 }
 
-fn print_help() {
-    println!("\nCommands:\n");
-    println!("  {}  -  print the current available actions", Style::default().bold().paint("actions"));
-    println!("  {}  -  show game rules", Style::default().bold().paint("rules"));
-    println!("  {}    -  toggles showing debug information", Style::default().bold().paint("debug"));
-    println!("  {}     -  quit the game", Style::default().bold().paint("exit"));
-    println!();
-}
+fn main() {
 
-fn print_actions(actions: IndexSet<u8>) {
-    print!("\nPlayer's Actions: ");
-    for action in actions {
-        print!("{} ", Style::default().bold().paint(convert_num(action)));
-    }
-    println!("\n");
-}
+    const TIME: u64 = 5; 
+    const HINT_TIME: u64 = 1;
+    // Deepest perft the game's perft command runs, depth 8 takes a few seconds in a release
+    // build and every depth beyond it roughly eight times as long
+    const MAX_PERFT_DEPTH: u32 = 8;
+    const WIDTH: u8 = 8;
+    const HEIGHT: u8 = 8;
 
-fn print_rules() {
-    println!("      #                {}                #\n", Style::default().bold().paint("REVERSI RULES"));
-    println!(" * {} tiles represent the user's spots, {} represent the CPUs.\n", Red.paint("Red"), Green.paint("Green"));
-    println!(" * The user starts by placing a tile adjacent to a green tile.\n Possible actions are marked by asterisks (*) on the board.\n");
-    println!(" * The game ends when either player cannot play a piece or the\n board is full.  The player with the most tiles wins.\n");
-}
+    let args: Vec<String> = std::env::args().collect();
 
-fn toggle_debug(debug: bool) -> bool {
-    if debug {
-        println!("Debug turned OFF");
-        false
-    } else {
-        println!("Debug turned ON");
-        true
+    match theme_from_args(&args) {
+        Ok(t) => theme::set_theme(t),
+        Err(e) => {
+            println!("ERROR: {}", e);
+            return;
+        }
     }
-}
-
-/**
- * Simplified Monte Carlo Tree Search which performs random playouts until completion 
- * and records the win/draw/loss statistics for each available action at current board state.
- *  Parameters:
- *      b              -    the current board state to initialize the playout board
- *      max_steps      -    maximum number of iterations 
- *      timer          -    maximum amount of time to spend during the mcts in seconds
- *      debug          -    used to print extra debug statements
- * 
- */
- fn monte_carlo_tree_search(b: &Board, max_steps: usize, timer: usize, diff: &String, debug: bool) -> u8 {
-
-    let mut stats: [Vec<u8>; 3] = [vec![], vec![], vec![]];
-    let start_time = Instant::now();
-    
-    if debug { println!("CPU performing {} random playouts...", max_steps); }
-    
-    for i in 0..max_steps {
-
-        if !debug { print!("."); stdout().flush(); }
-        if (i + 1) % 30 == 0 { println!() }
 
-        // Break out of function when timer is reached
-        if start_time.elapsed() >= Duration::new(timer as u64, 0) { 
-            let res: u64 =  i as u64 / start_time.elapsed().as_secs();
-            if debug { println!("Play-outs per second: {}", res); }
-            break;
+    // Networked games are between two people, so no CPU settings are needed
+    if let Some(port) = arg_value(&args, "--serve") {
+        match port.parse::<u16>() {
+            Ok(p) => net::serve(p),
+            Err(_) => println!("ERROR: invalid port: {}", port)
         }
-        
-        let actions = b.get_available_actions(debug);
-
-        if debug { println!("Step #{} | {:?}", i, actions); }
-        
-        for action in actions {
-
-            let mut playout_board: Board = b.clone();
+        return;
+    }
 
-            match random_playout(&mut playout_board, action, diff, debug) {
-                1 => stats[1].push(action), // 1 -> Player wins so add action to loss list
-                2 => stats[0].push(action), // 2 -> CPU wins so add action to win list
-                3 => stats[2].push(action), // 3 -> Game draw so add action to draw list
-                _ => continue
-            };
-        }
+    if let Some(addr) = arg_value(&args, "--watch") {
+        stream::watch(&addr);
+        return;
     }
 
-    // Populate hashmap with frequency of elements in win list
-    let mut a = HashMap::new();
-    for i in stats[0].iter() {
-        if a.contains_key(i) {
-            *(a.get_mut(&i).unwrap()) += 1;
-        } else {
-            a.insert(i, 1);
-        }
+    if let Some(addr) = arg_value(&args, "--connect") {
+        net::connect(&addr);
+        return;
     }
 
-    if debug {
-        println!("Player wins: {:?}", stats[1]);
-        println!("CPU wins: {:?}", stats[0]);
-        println!("Draws: {:?}", stats[2]);
-        for (pos, wins) in &a {
-            println!("{}: {}", pos, wins);
-        } 
-    }
-
-    // Returns the highest value in frequency hashmap as best play if win list exists,
-    // else return a random action if no elements exist in win list.
-    if stats[0].len() == 0 {
-        let actions = b.get_available_actions(debug);
-        let actions_size = actions.len();
-        let rand_index = rand::thread_rng().gen_range(0, actions_size);
-        let rand_val = actions.get_index(rand_index).unwrap();
-        return *rand_val;
+    // Render a saved game as SVG frames or an animated GIF next to it
+    if let Some(path) = arg_value(&args, "--svg") {
+        export_svg(&path);
+        return;
     }
-    
-    else {
-        **a.iter().max_by(|a, b| a.1.cmp(&b.1)).map(|(k, _v)| k).unwrap()
+    if let Some(path) = arg_value(&args, "--gif") {
+        export_gif(&path);
+        return;
     }
 
-}
+    // Write the positional network as a starting point for trained weights
+    if let Some(path) = arg_value(&args, "--write-network") {
+        match Network::positional().save(&path) {
+            Ok(()) => println!("Wrote the positional network to {}", path),
+            Err(e) => println!("ERROR: could not write network to {}: {}", path, e)
+        }
+        return;
+    }
 
+    // Load the optional value/policy network
+    let network: Option<Network> = match arg_value(&args, "--network") {
+        Some(path) => match Network::load(&path) {
+            Ok(net) => Some(net),
+            Err(e) => {
+                println!("ERROR: could not load network from {}: {}", path, e);
+                return;
+            }
+        },
+        None => None
+    };
 
-/**
-*   Performs random playouts or uses a heuristic to perform the next move based on the diff parameter.
-        - if diff is set to easy, then the playouts will be random actions 
-        - if diff is set to hard, playouts will use the Max Tile Heuristic
-*/
-fn random_playout(b: &mut Board, action: u8, diff: &String, debug: bool) -> u8 {
-    
-    if debug { println!("Playing action: {}", action); }
+    // Select the search algorithm, flat Monte Carlo unless PUCT is requested
+    let use_puct = match arg_value(&args, "--search").as_deref() {
+        None | Some("mcts") => false,
+        Some("puct") => true,
+        Some(other) => {
+            println!("ERROR: unknown search '{}', expected mcts or puct", other);
+            return;
+        }
+    };
 
-    // Play a game until completion
-    loop {
-        match b.check_game_state(debug) {
-            0 => { // Game not done
-                if !b.player_turn { 
-                    let actions = b.get_cpu_actions();
-                    let actions_size = actions.len();
-
-                    match diff.as_str() {
-                        // EASY
-                        "1" => {
-                            let rand_index = rand::thread_rng().gen_range(0, actions_size);
-                            let rand_val = actions.get_index(rand_index).unwrap();
-                            b.ins(*rand_val, 2, debug);
-                        },
-                        
-                        // HARD
-                        "2" => {
-                            let new_val = get_max_tile(b, debug);
-                            if new_val == 99 { continue; } // Someone ran out of moves
-                            if debug { println!("new_val: {}", new_val); }
-                            b.ins(new_val, 2, debug);
-                        }
-                        _ => println!("ERROR in random_playout() -> diff variable invalid: {}", diff)
-                    };
-                }
+    // Game clock for the CPU, --time <seconds> total with --increment <seconds> per move.
+    // Without a clock every move is searched for a fixed TIME.
+    let mut cpu_clock: Option<TimeManager> = match clock_from_args(&args) {
+        Ok(clock) => clock,
+        Err(e) => {
+            println!("ERROR: {}", e);
+            return;
+        }
+    };
 
-                else {
-                    let actions = b.get_player_actions();
-                    let actions_size = actions.len();
-                    let rand_index = rand::thread_rng().gen_range(0, actions_size);
-                    let rand_val = actions.get_index(rand_index).unwrap();
-                    b.ins(*rand_val, 1, debug);     
-                }
+    // Games start from --position <obf> if given, which the editor can change
+    let mut start: Option<Board> = match arg_value(&args, "--position").map(|p| Board::from_obf(&p)) {
+        Some(Ok(b)) => Some(b),
+        Some(Err(e)) => {
+            println!("ERROR: invalid position: {}", e);
+            return;
+        },
+        None => None
+    };
 
-                if debug { b.print(debug); }
-                continue;
-            },
-            1 => return 1, // Player Wins
-            2 => return 2, // CPU Wins
-            3 => return 3, // Draw
-            _ => return 42
-        };
+    // Count the game tree's leaves to check move generation, from --position if given
+    if let Some(depth) = arg_value(&args, "--perft") {
+        match depth.parse::<u32>() {
+            Ok(d) => print_perft(&start.unwrap_or_else(|| Board::new(WIDTH, HEIGHT)), d, args.iter().any(|a| a == "--divide")),
+            Err(_) => println!("ERROR: invalid perft depth: {}", depth)
+        }
+        return;
     }
-}
-
-/**
- * Max Tile Heuristic
- *      - Returns the position that results in the highest score out of all possible actions
- *      - If no actions are available, then return an error code of 99 to indicate game end                      
- */
-fn get_max_tile(b: &Board, debug: bool) -> u8 {
 
-    let actions = b.get_available_actions(debug);
-    let (prev_player_score, prev_cpu_score): (u8, u8) = b.get_score();
-    let best_score = prev_cpu_score;
-    let mut best_pos: u8 = 0;
-    
-    if debug { println!("{:?}", actions); }
-
-    if actions.len() == 0 {
-        return 99;
+    // Play built-in engines against each other, from --position if given
+    if let Some(engines) = arg_value(&args, "--match") {
+        if let Err(e) = run_match(&args, &engines, &start.unwrap_or_else(|| Board::new(WIDTH, HEIGHT))) {
+            println!("ERROR: {}", e);
+        }
+        return;
     }
 
-    for action in actions {
-        // check increase in value of tiles
-        let mut new_board: Board = b.clone();
-        
-        new_board.ins(action, 2, debug);
+    let puct_config = match puct_config_from_args(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("ERROR: {}", e);
+            return;
+        }
+    };
 
-        let (player_score, cpu_score): (u8, u8) = new_board.get_score();
+    // Protocol modes talk to a GUI or controller over stdin/stdout instead of the prompt loop
+    let nboard_mode = args.iter().any(|a| a == "--nboard");
+    let gtp_mode = args.iter().any(|a| a == "--gtp");
+    let http_addr = arg_value(&args, "--http");
+    let protocol_mode = nboard_mode || gtp_mode || http_addr.is_some();
+    let replay_path = arg_value(&args, "--replay");
 
-        if cpu_score > best_score {
-            best_pos = action;
-        }
+    if !protocol_mode && replay_path.is_none() {
+        print_title();
+        print_rules();
     }
-    
-    best_pos
-}
 
-fn main() {
+    if args.iter().any(|a| a == "--edit") && !protocol_mode && replay_path.is_none() {
+        match editor::run(start.unwrap_or_else(|| Board::new(WIDTH, HEIGHT))) {
+            Some(b) => start = Some(b),
+            None => return
+        }
+    }
 
-    const MAX_STEPS: usize = 1000;
-    const TIME: usize = 5; 
-    const WIDTH: u8 = 8;
-    const HEIGHT: u8 = 8;
+    // Moves played, for saving the game and marking the last move on exported images
+    let mut record = match &start {
+        Some(b) => GameRecord::from_position(b),
+        None => GameRecord::new()
+    };
 
-    print_title();
-    print_rules();
+    // Playout policies given on the command line replace the difficulty selection
+    let playout_arg = arg_value(&args, "--playout");
+    let player_playout_arg = arg_value(&args, "--player-playout");
 
     let mut cpu_diff = String::new();
 
     // Get difficulty
-    loop {
+    while playout_arg.is_none() && !protocol_mode && replay_path.is_none() {
         println!("\n[1] Easy");
         println!("[2] Hard\n");
         println!("Select CPU Difficulty (1, 2): ");
         io::stdin().read_line(&mut cpu_diff).expect("Failed to read line");
 
-        let difficulty: String = match cpu_diff.trim().to_string().as_str() {
-            "1" => {
-                cpu_diff.trim().to_string()
-            },
-            "2" => {
-                cpu_diff.trim().to_string()
-            },
+        match cpu_diff.trim() {
+            "1" | "2" => (),
             _ => {
                 println!("ERROR: Invalid entry");
                 cpu_diff = String::new();
@@ -900,11 +514,101 @@ fn main() {
         break;
     } 
 
-    let difficulty = cpu_diff.trim().to_string();
-    let mut board = Board::new(WIDTH, HEIGHT);
+    // Easy plays random playouts, hard uses the Max Tile Heuristic for the CPU's moves
+    let cpu_policy_name: String = match (&playout_arg, cpu_diff.trim()) {
+        (Some(name), _) => name.clone(),
+        (None, "2") => String::from("maxtile"),
+        (None, _) => String::from("uniform")
+    };
+    let player_policy_name: String = match (&player_playout_arg, &playout_arg) {
+        (Some(name), _) | (None, Some(name)) => name.clone(),
+        (None, None) => String::from("uniform")
+    };
+
+    let (cpu_policy, player_policy) = match (policy_from_name(&cpu_policy_name), policy_from_name(&player_policy_name)) {
+        (Ok(cpu), Ok(player)) => (cpu, player),
+        (Err(e), _) | (_, Err(e)) => {
+            println!("ERROR: {}", e);
+            return;
+        }
+    };
+
+    // Spectators follow the game and the CPU's search over a WebSocket
+    let broadcaster = match arg_value(&args, "--stream") {
+        Some(addr) => match Broadcaster::start(&addr, &record.start()) {
+            Ok(b) => {
+                println!("Streaming the game on ws://{}", addr);
+                Some(Rc::new(b))
+            },
+            Err(e) => {
+                println!("ERROR: could not stream on {}: {}", addr, e);
+                return;
+            }
+        },
+        None => None
+    };
+
+    // A loaded network replaces the CPU's playout policy
+    let mut mcts = MctsEngine::new(player_policy, cpu_policy);
+    if let Some(net) = network {
+        mcts = mcts.with_network(net);
+    }
+    if use_puct {
+        mcts = mcts.with_puct(puct_config);
+    }
+
+    // Spectators and the full-screen UI both follow the search
+    let tui_mode = args.iter().any(|a| a == "--tui");
+    let mut progress: Vec<ProgressCallback> = Vec::new();
+    if let Some(b) = &broadcaster {
+        let b = Rc::clone(b);
+        progress.push(Box::new(move |p: &SearchProgress| b.progress(p)));
+    }
+    if tui_mode {
+        progress.push(Box::new(tui::progress_reporter()));
+    }
+    if !progress.is_empty() {
+        mcts = mcts.with_progress(Box::new(move |p: &SearchProgress| progress.iter().for_each(|f| f(p))));
+    }
+    let engine: &mut dyn Engine = &mut mcts;
+
+    if nboard_mode {
+        nboard::run(engine);
+        return;
+    }
+
+    if gtp_mode {
+        gtp::run(engine);
+        return;
+    }
+
+    if let Some(addr) = http_addr {
+        http::serve(&addr, engine);
+        return;
+    }
+
+    if args.iter().any(|a| a == "--cpu-vs-cpu") {
+        cpu_vs_cpu(engine, record.start(), broadcaster.as_deref(), TIME);
+        return;
+    }
+
+    if let Some(path) = replay_path {
+        replay::run(&path, engine, TIME);
+        return;
+    }
+
+    if tui_mode {
+        tui::run(engine, record, cpu_clock.take(), broadcaster.as_deref(), TIME);
+        return;
+    }
+
+    let mut board = record.start();
     let re = Regex::new(r"([aA-hH][1-8])").unwrap();
     let mut debug = false;
 
+    // Summary of the CPU's last search for the pv command
+    let mut last_search: Option<String> = None;
+
     // =============
     // Player VS CPU
     // =============
@@ -912,34 +616,66 @@ fn main() {
         match board.check_game_state(debug) {
             1 => {
                 println!("Player has won");
-                board.print(debug);
+                board.print();
                 break;
             },
             2 => {
                 println!("CPU has won");
-                board.print(debug);
+                board.print();
                 break;
             },
             3 => {
                 println!("Game is a draw");
-                board.print(debug);
+                board.print();
                 break;
             },
             _ => ()
         };
 
-        board.print(true);
+        board.print();
 
-        if board.is_player_turn() == true {
+        if board.is_player_turn() {
             println!("Place piece at position: ");
             let mut input = String::new();
             io::stdin().read_line(&mut input).expect("Failed to read line");
+
+            // Checked before moves since file names may look like squares
+            if let Some(path) = input.trim().strip_prefix("svg ") {
+                let last_move = record.moves().last().copied().flatten();
+                let options = SvgOptions { last_move, ..SvgOptions::default() };
+                match fs::write(path.trim(), position_svg(&board, &options)) {
+                    Ok(()) => println!("Saved the position to {}\n", path.trim()),
+                    Err(e) => println!("ERROR: could not write {}: {}", path.trim(), e)
+                }
+                continue;
+            }
+            if let Some(depth) = input.trim().strip_prefix("perft ") {
+                match depth.trim().parse::<u32>() {
+                    Ok(d) if (1..=MAX_PERFT_DEPTH).contains(&d) => print_perft(&board, d, true),
+                    _ => println!("ERROR: perft depth must be a number from 1 to {}", MAX_PERFT_DEPTH)
+                }
+                continue;
+            }
+            if let Some(path) = input.trim().strip_prefix("save ") {
+                match record.save(path.trim()) {
+                    Ok(()) => println!("Saved the game to {}, replay it with --replay {}\n", path.trim(), path.trim()),
+                    Err(e) => println!("ERROR: could not write {}: {}", path.trim(), e)
+                }
+                continue;
+            }
             
             // Validate input string
             match re.is_match(&input) {
                 true => {
                     let input_u8: u8 = convert_2d(&input);
-                    board.ins(input_u8, 1, debug);
+                    match record.play(&mut board, input_u8) {
+                        Ok(()) => {
+                            if let Some(b) = &broadcaster {
+                                b.board(&board, 1, input_u8);
+                            }
+                        },
+                        Err(e) => println!("ERROR: {}", e)
+                    }
                 },
                 false => {
                     match input.as_str() {
@@ -951,12 +687,39 @@ fn main() {
                             print_actions(board.get_player_actions());
                             continue;
                         },
+                        "pv\n" => {
+                            match &last_search {
+                                Some(summary) => println!("\n{}\n", summary),
+                                None => println!("The CPU has not moved yet")
+                            }
+                            continue;
+                        },
+                        "hint\n" => {
+                            engine.set_position(&board);
+                            if let Some(best) = engine.choose_move(&MoveBudget::fixed(HINT_TIME)) {
+                                let win_rate = engine.statistics()
+                                    .and_then(|r| r.moves.iter().find(|m| m.action == best))
+                                    .map_or(0.5, |m| m.value);
+                                board.print_annotated(&[(best, theme::current().hint)]);
+                                println!("Hint: {} ({:.0}% estimated chance to win)\n", convert_num(best), win_rate * 100.0);
+                            }
+                            continue;
+                        },
+                        "analyze\n" | "analyze board\n" => {
+                            engine.set_position(&board);
+                            engine.choose_move(&MoveBudget::fixed(TIME));
+                            if let Some(result) = engine.statistics() {
+                                print_analysis(&board, result, input.starts_with("analyze board"));
+                            }
+                            continue;
+                        },
                         "rules\n" => {
                             print_rules();
                             continue;
                         }
                         "debug\n" => {
                             debug = toggle_debug(debug);
+                            engine.set_debug(debug);
                             continue;
                         },
                         "exit\n" => break,
@@ -969,9 +732,82 @@ fn main() {
                 }
             };
         } else {
-            let best_play: u8 = monte_carlo_tree_search(&board, MAX_STEPS, TIME, &difficulty, debug);
+            let budget = match &cpu_clock {
+                Some(clock) => {
+                    let (player_score, cpu_score) = board.get_score();
+                    clock.allocate(64 - player_score - cpu_score)
+                },
+                None => MoveBudget::fixed(TIME)
+            };
+
+            let move_start = Instant::now();
+            engine.set_position(&board);
+            if let Some(b) = &broadcaster {
+                b.follow_search(true);
+            }
+            let best_play = engine.choose_move(&budget);
+            if let Some(b) = &broadcaster {
+                b.follow_search(false);
+            }
+            let best_play: u8 = match best_play {
+                Some(pos) => pos,
+                None => break
+            };
+
+            if let Some(clock) = cpu_clock.as_mut() {
+                clock.record(move_start.elapsed());
+                let left = clock.remaining().as_secs();
+                println!("\n\nCPU clock: {}:{:02} remaining", left / 60, left % 60);
+            }
             println!("\n\nCPU found {} as best play", convert_num(best_play));
-            board.ins(best_play, 2, debug);
+            last_search = engine.statistics().map(search_summary);
+            if let Some(summary) = &last_search {
+                println!("{}", summary);
+            }
+            if let Err(e) = record.play(&mut board, best_play) {
+                println!("ERROR: {}", e);
+                break;
+            }
+            if let Some(b) = &broadcaster {
+                b.board(&board, 2, best_play);
+            }
         }     
     }   
+}
+
+/**
+ * Plays the CPU against itself from start, searching secs seconds per move with each side's
+ * playout policy
+ */
+fn cpu_vs_cpu(engine: &mut dyn Engine, start: Board, broadcaster: Option<&Broadcaster>, secs: u64) {
+    let mut board = start;
+
+    // Both sides are the CPU, so every search is streamed
+    if let Some(b) = broadcaster {
+        b.follow_search(true);
+    }
+
+    while board.check_game_state(false) == 0 {
+        board.print();
+
+        let val: u8 = if board.is_player_turn() { 1 } else { 2 };
+        engine.set_position(&board);
+        let best_play = match engine.choose_move(&MoveBudget::fixed(secs)) {
+            Some(pos) => pos,
+            None => break
+        };
+
+        println!("\n\n{} plays {}", if val == 1 { "Player" } else { "CPU" }, convert_num(best_play));
+        board.ins(best_play, val, false);
+        if let Some(b) = broadcaster {
+            b.board(&board, val, best_play);
+        }
+    }
+
+    board.print();
+    match board.check_game_state(false) {
+        1 => println!("Player has won"),
+        2 => println!("CPU has won"),
+        _ => println!("Game is a draw")
+    }
 }
\ No newline at end of file
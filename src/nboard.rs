@@ -0,0 +1,260 @@
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+use reversi::clock::MoveBudget;
+use reversi::{convert_num, parse_pos, Board, Engine, SearchResult};
+
+const ENGINE_NAME: &str = "ReversiMCTS";
+
+/**
+ * NBoard has no notion of search time, so `set depth <n>` searches for n seconds
+ */
+const DEFAULT_DEPTH: u64 = 5;
+const MAX_DEPTH: u64 = 60;
+
+/**
+ * Runs the NBoard engine protocol over stdin/stdout until stdin is closed
+ *
+ * Supported commands:
+ *      nboard <version>    -   replies with the engine name
+ *      set depth <n>       -   search for n seconds per move
+ *      set game <ggf>      -   replaces the game with a GGF game record
+ *      move <mv>[/...]     -   plays a move (PA to pass) in the current game
+ *      go                  -   searches and replies with `=== <mv>`
+ *      hint <n>            -   searches and replies with the n best moves
+ *      ping <n>            -   replies `pong <n>`
+ *      learn               -   replies `learned`, there is no book to learn into
+ *
+ * Unknown commands and settings (such as `set contempt`) are ignored as the protocol requires.
+ * Black is the Player (1) and White the CPU (2), the engine plays whichever side is to move.
+ */
+pub fn run(engine: &mut dyn Engine) {
+    let stdin = io::stdin();
+    let mut board = Board::new(8, 8);
+    let mut depth: u64 = DEFAULT_DEPTH;
+
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(_) => break
+        };
+
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("nboard") => respond(&format!("set myname {}", ENGINE_NAME)),
+            Some("set") => match words.next() {
+                Some("depth") => match words.next().and_then(|d| d.parse::<u64>().ok()) {
+                    Some(d) => depth = d.clamp(1, MAX_DEPTH),
+                    None => respond("status invalid depth")
+                },
+                Some("game") => {
+                    let ggf = line.splitn(3, char::is_whitespace).nth(2).unwrap_or("");
+                    match parse_ggf(ggf) {
+                        Ok(b) => board = b,
+                        Err(e) => respond(&format!("status {}", e))
+                    }
+                },
+                _ => ()
+            },
+            Some("move") => {
+                let mv = words.next().unwrap_or("").split('/').next().unwrap_or("");
+                if let Err(e) = play_move(&mut board, mv) {
+                    respond(&format!("status {}", e));
+                }
+            },
+            Some("go") => {
+                respond("status thinking");
+                let reply = match search(engine, &board, depth) {
+                    Some(result) => {
+                        let value = result.best_stats().map_or(0.5, |stats| stats.value);
+                        format!("=== {}/{:.1}", convert_num(result.best), eval(value))
+                    },
+                    None => String::from("=== PA")
+                };
+                respond(&reply);
+                respond("status");
+            },
+            Some("hint") => {
+                let n: usize = words.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+                respond("status thinking");
+                if let Some(result) = search(engine, &board, depth) {
+                    for stats in result.moves.iter().take(n) {
+                        respond(&format!("search {} {:.1} 0 {}", convert_num(stats.action), eval(stats.value), depth));
+                    }
+                }
+                respond("status");
+            },
+            Some("ping") => respond(&format!("pong {}", words.next().unwrap_or("0"))),
+            Some("learn") => respond("learned"),
+            _ => ()
+        }
+    }
+}
+
+fn respond(msg: &str) {
+    println!("{}", msg);
+    io::stdout().flush().unwrap();
+}
+
+/**
+ * Searches for the side to move, or returns None if it has to pass
+ */
+fn search<'a>(engine: &'a mut dyn Engine, board: &Board, depth: u64) -> Option<&'a SearchResult> {
+    engine.set_position(board);
+    engine.choose_move(&MoveBudget::fixed(depth))?;
+    engine.statistics()
+}
+
+/**
+ * The searches estimate win rates rather than disc counts, so NBoard is sent the win
+ * rate scaled onto the disc range: 100% => +64, 50% => 0, 0% => -64
+ */
+fn eval(value: f32) -> f32 {
+    (2.0 * value - 1.0) * 64.0
+}
+
+/**
+ * Plays a move in NBoard notation (e.g. F5, or PA to pass) for the side to move
+ */
+fn play_move(board: &mut Board, mv: &str) -> Result<(), String> {
+    let actions = board.get_available_actions(false);
+
+    if mv.eq_ignore_ascii_case("PA") {
+        if !actions.is_empty() {
+            return Err(String::from("illegal pass, the side to move has actions"));
+        }
+        board.pass();
+        return Ok(());
+    }
+
+    match parse_pos(mv) {
+        Some(pos) if actions.contains(&pos) => {
+            let val: u8 = if board.is_player_turn() { 1 } else { 2 };
+            board.ins(pos, val, false);
+            Ok(())
+        },
+        _ => Err(format!("illegal move {}", mv))
+    }
+}
+
+/**
+ * Builds the board from a GGF game record, e.g.
+ *      (;GM[Othello]PC[NBoard]TY[8]BO[8 ---...--- *]B[F5]W[D6//1.2];)
+ *
+ * BO sets up the starting position, which need not be the standard one. Its squares may be
+ * written in one run or as rows separated by spaces.
+ */
+fn parse_ggf(ggf: &str) -> Result<Board, String> {
+    let mut board = Board::new(8, 8);
+
+    for (tag, value) in ggf_tags(ggf) {
+        match tag.as_str() {
+            "BO" => {
+                let mut parts = value.split_whitespace();
+                if parts.next() != Some("8") {
+                    return Err(String::from("only 8x8 boards are supported"));
+                }
+
+                // The 64 squares are followed by the side to move
+                let mut chars = parts.flat_map(|p| p.chars());
+                let squares: Vec<u8> = chars.by_ref().take(64).map(|c| match c {
+                    '*' | 'X' | 'x' | '#' => Ok(1),
+                    'O' | 'o' => Ok(2),
+                    '-' | '.' => Ok(0),
+                    _ => Err(format!("invalid square '{}' in BO", c))
+                }).collect::<Result<_, _>>()?;
+                let black_to_move = match chars.next() {
+                    Some('*') | Some('X') | Some('x') | Some('#') => true,
+                    Some('O') | Some('o') => false,
+                    _ => return Err(String::from("missing side to move in BO"))
+                };
+
+                board = Board::from_squares(&squares, black_to_move)?;
+            },
+            "B" | "W" => {
+                let to_move = if board.is_player_turn() { "B" } else { "W" };
+                if tag != to_move {
+                    return Err(format!("{} moved out of turn", tag));
+                }
+                play_move(&mut board, value.split('/').next().unwrap_or(""))?;
+            },
+            _ => ()
+        }
+    }
+
+    Ok(board)
+}
+
+/**
+ * Splits a GGF record into its (tag, value) pairs in order
+ */
+fn ggf_tags(ggf: &str) -> Vec<(String, String)> {
+    let mut tags = Vec::new();
+    let mut tag = String::new();
+    let mut chars = ggf.chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_ascii_uppercase() {
+            tag.push(c);
+        } else if c == '[' {
+            let value: String = chars.by_ref().take_while(|v| *v != ']').collect();
+            tags.push((tag.clone(), value));
+            tag.clear();
+        } else {
+            tag.clear();
+        }
+    }
+
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "---------------------------O*------*O---------------------------";
+
+    #[test]
+    fn ggf_tags_in_order() {
+        let tags = ggf_tags("(;GM[Othello]PC[NBoard]BO[8 -- *]B[F5//1.2]W[d6];)");
+        let expected = [("GM", "Othello"), ("PC", "NBoard"), ("BO", "8 -- *"), ("B", "F5//1.2"), ("W", "d6")];
+
+        assert_eq!(tags.len(), expected.len());
+        for ((tag, value), (t, v)) in tags.iter().zip(expected.iter()) {
+            assert_eq!((tag.as_str(), value.as_str()), (*t, *v));
+        }
+
+        assert!(ggf_tags("(;;)").is_empty());
+    }
+
+    #[test]
+    fn ggf_board_in_one_run() {
+        let b = parse_ggf(&format!("(;GM[Othello]BO[8 {} *]B[F5]W[d6];)", START)).unwrap();
+
+        let mut expected = Board::new(8, 8);
+        play_move(&mut expected, "f5").unwrap();
+        play_move(&mut expected, "d6").unwrap();
+        assert_eq!(b.squares(), expected.squares());
+        assert!(b.is_player_turn());
+    }
+
+    #[test]
+    fn ggf_board_in_rows() {
+        let rows: Vec<&str> = (0..8).map(|r| &START[r * 8..(r + 1) * 8]).collect();
+        let b = parse_ggf(&format!("(;BO[8 {} O];)", rows.join(" "))).unwrap();
+
+        assert_eq!(b.squares(), Board::new(8, 8).squares());
+        assert!(!b.is_player_turn());
+    }
+
+    #[test]
+    fn ggf_errors() {
+        assert!(parse_ggf(&format!("(;BO[10 {} *];)", START)).is_err());
+        assert!(parse_ggf(&format!("(;BO[8 {}];)", START)).is_err());
+        assert!(parse_ggf("(;BO[8 ---- *];)").is_err());
+        assert!(parse_ggf(&format!("(;BO[8 {} *]W[F5];)", START)).is_err());
+        assert!(parse_ggf("(;B[A1];)").is_err());
+    }
+}
@@ -0,0 +1,428 @@
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use reversi::theme;
+use reversi::theme::Theme;
+use reversi::{convert_num, parse_pos, Board};
+
+const PROTOCOL: &str = "HELLO reversi 1";
+const RECONNECT_ATTEMPTS: u32 = 30;
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+
+/**
+ * Input for the game loop, from the local player or the peer's connection
+ *
+ * Remote and Dropped carry the number of the connection they came from, so lines from a
+ * connection that has since been replaced are ignored.
+ */
+enum Event {
+    Local(String),
+    Remote(u32, String),
+    Dropped(u32)
+}
+
+/**
+ * Which end of the connection this instance is
+ *
+ * The server plays the Player's (1) discs and moves first, the client plays the CPU's (2).
+ */
+enum Role {
+    Server(TcpListener),
+    Client(String)
+}
+
+impl Role {
+
+    fn side(&self) -> u8 {
+        match self {
+            Role::Server(_) => 1,
+            Role::Client(_) => 2
+        }
+    }
+
+    /**
+     * Waits for the peer (server) or connects to it, retrying for a while (client)
+     *
+     * Local input from rx is watched while waiting so the player can leave with exit.
+     * @returns:    None if the player left before the peer was connected
+     */
+    fn open(&self, rx: &Receiver<Event>) -> io::Result<Option<TcpStream>> {
+        match self {
+            Role::Server(listener) => {
+                listener.set_nonblocking(true)?;
+                loop {
+                    match listener.accept() {
+                        Ok((stream, addr)) => {
+                            stream.set_nonblocking(false)?;
+                            println!("{} connected", addr);
+                            return Ok(Some(stream));
+                        },
+                        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                            if left(rx, ACCEPT_POLL) {
+                                return Ok(None);
+                            }
+                        },
+                        Err(e) => return Err(e)
+                    }
+                }
+            },
+            Role::Client(addr) => {
+                let mut attempt = 1;
+                loop {
+                    match TcpStream::connect(addr) {
+                        Ok(stream) => return Ok(Some(stream)),
+                        Err(e) if attempt >= RECONNECT_ATTEMPTS => return Err(e),
+                        Err(_) => {
+                            attempt += 1;
+                            if left(rx, RECONNECT_DELAY) {
+                                return Ok(None);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Hosts a networked game on port and waits for an opponent to connect
+ */
+pub fn serve(port: u16) {
+    match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(listener) => {
+            println!("Waiting for an opponent on port {} (enter 'exit' to leave)...", port);
+            run(Role::Server(listener));
+        },
+        Err(e) => println!("ERROR: could not listen on port {}: {}", port, e)
+    }
+}
+
+/**
+ * Joins a networked game hosted at addr (host:port)
+ */
+pub fn connect(addr: &str) {
+    println!("Connecting to {}...", addr);
+    run(Role::Client(String::from(addr)));
+}
+
+/**
+ * Game loop of a networked game
+ *
+ * Line protocol, one message per line:
+ *      HELLO reversi 1     -   sent by both ends when a connection opens
+ *      MOVES <mv>...       -   every move of the game so far, sent after HELLO so a
+ *                              reconnecting peer can catch up
+ *      MOVE <mv>           -   a move, validated against the available actions on receipt
+ *      ILLEGAL <mv>        -   the peer rejected a move
+ *      RESIGN              -   the sender resigns
+ *      DRAW?               -   the sender offers a draw, answered by DRAW YES or DRAW NO
+ *      BYE                 -   the sender left the game
+ */
+fn run(role: Role) {
+    let me = role.side();
+    let (tx, rx) = channel();
+
+    // Local input is read on its own thread so the peer's messages are handled while waiting
+    let local_tx = tx.clone();
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+            match line {
+                Ok(l) => if local_tx.send(Event::Local(l)).is_err() { break },
+                Err(_) => break
+            }
+        }
+    });
+
+    let mut board = Board::new(8, 8);
+    let mut moves: Vec<u8> = Vec::new();
+    let mut connection: u32 = 0;
+    let mut peer_offered_draw = false;
+
+    let mut stream = match open_connection(&role, connection, &tx, &rx, &moves) {
+        Ok(Some(s)) => s,
+        Ok(None) => return,
+        Err(e) => {
+            println!("ERROR: could not connect: {}", e);
+            return;
+        }
+    };
+
+    let theme: Theme = theme::current();
+    println!("You are {}", if me == 1 { format!("{} and move first", theme.player_name) } else { theme.cpu_name.to_string() });
+    show(&board, me);
+
+    while board.check_game_state(false) == 0 {
+        let event = match rx.recv() {
+            Ok(e) => e,
+            Err(_) => break
+        };
+
+        match event {
+            Event::Local(line) => {
+                let input = line.trim().to_ascii_lowercase();
+
+                match input.as_str() {
+                    "resign" => {
+                        send(&mut stream, "RESIGN");
+                        println!("You resigned");
+                        return;
+                    },
+                    "draw" => {
+                        send(&mut stream, "DRAW?");
+                        println!("Draw offered, waiting for the opponent");
+                    },
+                    "yes" | "no" if peer_offered_draw => {
+                        peer_offered_draw = false;
+                        if input == "yes" {
+                            send(&mut stream, "DRAW YES");
+                            println!("Game is a draw by agreement");
+                            return;
+                        }
+                        send(&mut stream, "DRAW NO");
+                    },
+                    "exit" => {
+                        send(&mut stream, "BYE");
+                        return;
+                    },
+                    _ => {
+                        let to_move: u8 = if board.is_player_turn() { 1 } else { 2 };
+                        match parse_pos(&input) {
+                            _ if to_move != me => println!("Wait for your opponent's move"),
+                            Some(pos) if board.get_available_actions(false).contains(&pos) => {
+                                board.ins(pos, me, false);
+                                moves.push(pos);
+                                send(&mut stream, &format!("MOVE {}", convert_num(pos)));
+                                show(&board, me);
+                            },
+                            _ => println!("ERROR: invalid input, enter a move, 'draw', 'resign' or 'exit'")
+                        }
+                    }
+                }
+            },
+            Event::Remote(n, line) if n == connection => {
+                let mut words = line.split_whitespace();
+
+                match (words.next(), words.next()) {
+                    (Some("HELLO"), _) => {
+                        if line.trim() != PROTOCOL {
+                            println!("ERROR: opponent speaks a different protocol: {}", line);
+                            return;
+                        }
+                    },
+                    (Some("MOVES"), _) => {
+                        let peer_moves: Option<Vec<u8>> = line.split_whitespace().skip(1).map(parse_pos).collect();
+                        match peer_moves.ok_or_else(|| String::from("invalid move list")).and_then(|m| catch_up(&mut board, &mut moves, &m)) {
+                            Ok(true) => show(&board, me),
+                            Ok(false) => (),
+                            Err(e) => {
+                                println!("ERROR: games out of sync: {}", e);
+                                send(&mut stream, "BYE");
+                                return;
+                            }
+                        }
+                    },
+                    (Some("MOVE"), Some(mv)) => {
+                        let to_move: u8 = if board.is_player_turn() { 1 } else { 2 };
+                        match parse_pos(mv) {
+                            Some(pos) if to_move != me && board.get_available_actions(false).contains(&pos) => {
+                                board.ins(pos, to_move, false);
+                                moves.push(pos);
+                                println!("\nOpponent played {}", convert_num(pos));
+                                show(&board, me);
+                            },
+                            _ => {
+                                println!("Opponent sent an illegal move: {}", mv);
+                                send(&mut stream, &format!("ILLEGAL {}", mv));
+                            }
+                        }
+                    },
+                    (Some("ILLEGAL"), Some(mv)) => println!("ERROR: opponent rejected {}, the games are out of sync", mv),
+                    (Some("RESIGN"), _) => {
+                        println!("Opponent resigned, you win!");
+                        return;
+                    },
+                    (Some("DRAW?"), _) => {
+                        peer_offered_draw = true;
+                        println!("Opponent offers a draw, accept? (yes/no)");
+                    },
+                    (Some("DRAW"), Some("YES")) => {
+                        println!("Game is a draw by agreement");
+                        return;
+                    },
+                    (Some("DRAW"), Some("NO")) => println!("Opponent declined the draw"),
+                    (Some("BYE"), _) => {
+                        println!("Opponent left the game");
+                        return;
+                    },
+                    _ => println!("ERROR: unknown message from opponent: {}", line)
+                }
+            },
+            Event::Dropped(n) if n == connection => {
+                println!("Connection lost, reconnecting (enter 'exit' to leave)...");
+                connection += 1;
+                stream = match open_connection(&role, connection, &tx, &rx, &moves) {
+                    Ok(Some(s)) => s,
+                    Ok(None) => return,
+                    Err(e) => {
+                        println!("ERROR: could not reconnect: {}", e);
+                        return;
+                    }
+                };
+                println!("Reconnected");
+            },
+
+            // Lines from a connection that has been replaced
+            _ => ()
+        }
+    }
+
+    let (player_score, cpu_score) = board.get_score();
+    let (mine, theirs) = if me == 1 { (player_score, cpu_score) } else { (cpu_score, player_score) };
+    if mine > theirs {
+        println!("You won {} to {}", mine, theirs);
+    } else if theirs > mine {
+        println!("You lost {} to {}", mine, theirs);
+    } else {
+        println!("Game is a draw");
+    }
+}
+
+/**
+ * Opens a connection, starts reading it into the event channel, and sends HELLO and the
+ * moves played so far
+ *
+ * @returns:    None if the player left while waiting for the peer
+ */
+fn open_connection(role: &Role, connection: u32, tx: &Sender<Event>, rx: &Receiver<Event>, moves: &[u8]) -> io::Result<Option<TcpStream>> {
+    let mut stream = match role.open(rx)? {
+        Some(s) => s,
+        None => return Ok(None)
+    };
+    let reader = BufReader::new(stream.try_clone()?);
+    let remote_tx = tx.clone();
+
+    thread::spawn(move || {
+        for line in reader.lines() {
+            match line {
+                Ok(l) => if remote_tx.send(Event::Remote(connection, l)).is_err() { return },
+                Err(_) => break
+            }
+        }
+        let _ = remote_tx.send(Event::Dropped(connection));
+    });
+
+    let list: Vec<String> = moves.iter().map(|m| convert_num(*m)).collect();
+    writeln!(stream, "{}", PROTOCOL)?;
+    writeln!(stream, "MOVES {}", list.join(" "))?;
+
+    Ok(Some(stream))
+}
+
+/**
+ * Waits up to timeout for local input while there is no connection, lines from a dropped
+ * connection are discarded
+ *
+ * @returns:    true if the player entered exit
+ */
+fn left(rx: &Receiver<Event>, timeout: Duration) -> bool {
+    match rx.recv_timeout(timeout) {
+        Ok(Event::Local(line)) if line.trim().eq_ignore_ascii_case("exit") => true,
+        Ok(Event::Local(_)) => {
+            println!("Waiting for the opponent, enter 'exit' to leave");
+            false
+        },
+        _ => false
+    }
+}
+
+/**
+ * Plays any moves the peer has that this end is missing
+ *
+ * @returns:    Ok(true) if moves were played, Ok(false) if this end was up to date, or an
+ *              error if the two move lists disagree or contain an illegal move
+ */
+fn catch_up(board: &mut Board, moves: &mut Vec<u8>, peer_moves: &[u8]) -> Result<bool, String> {
+    let common = moves.len().min(peer_moves.len());
+    if moves[..common] != peer_moves[..common] {
+        return Err(String::from("the move lists differ"));
+    }
+
+    for pos in &peer_moves[common..] {
+        let val: u8 = if board.is_player_turn() { 1 } else { 2 };
+        if !board.get_available_actions(false).contains(pos) {
+            return Err(format!("illegal move {}", convert_num(*pos)));
+        }
+        board.ins(*pos, val, false);
+        moves.push(*pos);
+    }
+
+    Ok(peer_moves.len() > common)
+}
+
+/**
+ * Sends a message, a failed write is noticed by the reader thread as a dropped connection
+ */
+fn send(stream: &mut TcpStream, msg: &str) {
+    let _ = writeln!(stream, "{}", msg);
+}
+
+fn show(board: &Board, me: u8) {
+    board.print();
+    let to_move: u8 = if board.is_player_turn() { 1 } else { 2 };
+    if to_move == me {
+        println!("Your move: ");
+    } else {
+        println!("Waiting for the opponent...");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn squares(names: &[&str]) -> Vec<u8> {
+        names.iter().map(|n| parse_pos(n).unwrap()).collect()
+    }
+
+    fn play(names: &[&str]) -> (Board, Vec<u8>) {
+        let mut board = Board::new(8, 8);
+        let moves = squares(names);
+        for pos in &moves {
+            let val: u8 = if board.is_player_turn() { 1 } else { 2 };
+            board.ins(*pos, val, false);
+        }
+        (board, moves)
+    }
+
+    #[test]
+    fn catch_up_plays_the_missing_move() {
+        let (mut board, mut moves) = play(&["f5", "d6"]);
+        let (peer_board, peer_moves) = play(&["f5", "d6", "c3"]);
+
+        assert_eq!(catch_up(&mut board, &mut moves, &peer_moves), Ok(true));
+        assert_eq!(moves, peer_moves);
+        assert_eq!(board.squares(), peer_board.squares());
+        assert_eq!(board.is_player_turn(), peer_board.is_player_turn());
+
+        // The end that was ahead has nothing to catch up on
+        let (mut ahead, mut ahead_moves) = play(&["f5", "d6", "c3"]);
+        assert_eq!(catch_up(&mut ahead, &mut ahead_moves, &squares(&["f5", "d6"])), Ok(false));
+        assert_eq!(ahead_moves, peer_moves);
+    }
+
+    #[test]
+    fn catch_up_rejects_diverging_games() {
+        let (mut board, mut moves) = play(&["f5", "d6"]);
+        assert!(catch_up(&mut board, &mut moves, &squares(&["f5", "f6", "e6"])).is_err());
+
+        let (mut board, mut moves) = play(&["f5"]);
+        assert!(catch_up(&mut board, &mut moves, &squares(&["f5", "a1"])).is_err());
+    }
+}
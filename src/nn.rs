@@ -0,0 +1,231 @@
+use std::fs;
+use std::io;
+
+use rand::Rng;
+
+use crate::{Board, WEIGHTS};
+
+/**
+ * Number of network inputs:
+ *      - 64 squares holding the side to move's discs
+ *      - 64 squares holding the opponent's discs
+ *      - 64 squares marking the legal moves of the side to move
+ *      - 1 input that is set when the Player (1) is the side to move
+ */
+const INPUTS: usize = 64 * 3 + 1;
+const SQUARES: usize = 64;
+
+/**
+ * Small value/policy network evaluated on the CPU
+ *
+ * A single hidden layer MLP with two heads:
+ *      value  - probability that the side to move wins from this position
+ *      policy - a prior for each of the 64 squares, normalized over the legal moves
+ *
+ * Weights are loaded from a plain text file starting with the header
+ * `reversi-mlp <hidden>` followed by whitespace separated floats in the order
+ * w1 (hidden x 193), b1 (hidden), value weights (hidden), value bias (1),
+ * policy weights (64 x hidden), policy biases (64).
+ *
+ * No trained weights are shipped, `Network::positional()` builds a network playing the
+ * positional weight table that `save()` writes as a starting point for training.
+ */
+pub struct Network {
+    hidden: usize,
+    w1: Vec<f32>,
+    b1: Vec<f32>,
+    wv: Vec<f32>,
+    bv: f32,
+    wp: Vec<f32>,
+    bp: Vec<f32>
+}
+
+impl Network {
+
+    /**
+     * Loads network weights from a file in the format described above
+     */
+    pub fn load(path: &str) -> io::Result<Network> {
+        Network::parse(&fs::read_to_string(path)?)
+    }
+
+    /**
+     * Parses network weights in the format described above
+     */
+    pub fn parse(contents: &str) -> io::Result<Network> {
+        let mut tokens = contents.split_whitespace();
+
+        if tokens.next() != Some("reversi-mlp") {
+            return Err(invalid_data("missing 'reversi-mlp' header"));
+        }
+
+        let hidden: usize = match tokens.next().map(|t| t.parse()) {
+            Some(Ok(h)) if h > 0 => h,
+            _ => return Err(invalid_data("invalid hidden layer size"))
+        };
+
+        let mut values: Vec<f32> = Vec::new();
+        for token in tokens {
+            match token.parse() {
+                Ok(v) => values.push(v),
+                Err(_) => return Err(invalid_data(&format!("invalid weight '{}'", token)))
+            }
+        }
+
+        let expected = hidden * INPUTS + hidden + hidden + 1 + SQUARES * hidden + SQUARES;
+        if values.len() != expected {
+            return Err(invalid_data(&format!("expected {} weights, found {}", expected, values.len())));
+        }
+
+        let mut rest = values.into_iter();
+        let mut take = |n: usize| -> Vec<f32> { rest.by_ref().take(n).collect() };
+
+        let w1 = take(hidden * INPUTS);
+        let b1 = take(hidden);
+        let wv = take(hidden);
+        let bv = take(1)[0];
+        let wp = take(SQUARES * hidden);
+        let bp = take(SQUARES);
+
+        Ok(Network { hidden, w1, b1, wv, bv, wp, bp })
+    }
+
+    /**
+     * Network playing the positional weight table, with 3 hidden units:
+     *      0   -   always 1, carrying the policy logits WEIGHTS / 20 of each square
+     *      1   -   the weighted disc difference / 100 for the side to move when positive
+     *      2   -   the same when negative, so the value is a sigmoid of the difference
+     */
+    pub fn positional() -> Network {
+        let hidden = 3;
+        let mut w1 = vec![0.0_f32; hidden * INPUTS];
+        let mut wp = vec![0.0_f32; SQUARES * hidden];
+
+        for (sq, weight) in WEIGHTS.iter().enumerate() {
+            let w = *weight as f32 / 100.0;
+            w1[INPUTS + sq] = w;
+            w1[INPUTS + SQUARES + sq] = -w;
+            w1[2 * INPUTS + sq] = -w;
+            w1[2 * INPUTS + SQUARES + sq] = w;
+            wp[sq * hidden] = *weight as f32 / 20.0;
+        }
+
+        Network {
+            hidden,
+            w1,
+            b1: vec![1.0, 0.0, 0.0],
+            wv: vec![0.0, 1.0, -1.0],
+            bv: 0.0,
+            wp,
+            bp: vec![0.0; SQUARES]
+        }
+    }
+
+    /**
+     * Writes the network weights to a file in the format load() reads
+     */
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let line = |values: &[f32]| values.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(" ");
+
+        let mut contents = format!("reversi-mlp {}\n", self.hidden);
+        for row in self.w1.chunks(INPUTS) {
+            contents.push_str(&format!("{}\n", line(row)));
+        }
+        contents.push_str(&format!("{}\n{}\n{}\n", line(&self.b1), line(&self.wv), self.bv));
+        for row in self.wp.chunks(self.hidden) {
+            contents.push_str(&format!("{}\n", line(row)));
+        }
+        contents.push_str(&format!("{}\n", line(&self.bp)));
+
+        fs::write(path, contents)
+    }
+
+    /**
+     * Runs the network on a board position
+     *
+     * @returns:    (win probability for the side to move, priors for each legal move)
+     *              The priors sum to 1, and are empty if the side to move has no actions
+     */
+    pub fn evaluate(&self, b: &Board) -> (f32, Vec<(u8, f32)>) {
+        let input = encode(b);
+
+        // Hidden layer with ReLU activation
+        let mut hidden = vec![0.0_f32; self.hidden];
+        for (h, out) in hidden.iter_mut().enumerate() {
+            let row = &self.w1[h * INPUTS..(h + 1) * INPUTS];
+            let sum: f32 = row.iter().zip(input.iter()).map(|(w, x)| w * x).sum();
+            *out = (sum + self.b1[h]).max(0.0);
+        }
+
+        // Value head squashed into a win probability
+        let v: f32 = self.wv.iter().zip(hidden.iter()).map(|(w, x)| w * x).sum::<f32>() + self.bv;
+        let value = 1.0 / (1.0 + (-v).exp());
+
+        // Policy head, softmax over the legal moves only
+        let actions = b.get_available_actions(false);
+        let logits: Vec<(u8, f32)> = actions.iter().map(|&a| {
+            let sq: usize = a.into();
+            let row = &self.wp[sq * self.hidden..(sq + 1) * self.hidden];
+            let logit: f32 = row.iter().zip(hidden.iter()).map(|(w, x)| w * x).sum::<f32>() + self.bp[sq];
+            (a, logit)
+        }).collect();
+
+        let max_logit = logits.iter().map(|(_, l)| *l).fold(f32::NEG_INFINITY, f32::max);
+        let exps: Vec<(u8, f32)> = logits.iter().map(|(a, l)| (*a, (l - max_logit).exp())).collect();
+        let total: f32 = exps.iter().map(|(_, e)| e).sum();
+        let priors = exps.into_iter().map(|(a, e)| (a, e / total)).collect();
+
+        (value, priors)
+    }
+
+    /**
+     * Samples a move for the side to move in proportion to the policy priors
+     *
+     * @returns:    None if the side to move has no actions
+     */
+    pub fn sample_move(&self, b: &Board) -> Option<u8> {
+        let (_value, priors) = self.evaluate(b);
+        let mut r: f32 = rand::thread_rng().gen();
+
+        for (action, prior) in priors.iter() {
+            if r < *prior {
+                return Some(*action);
+            }
+            r -= prior;
+        }
+
+        // Rounding errors can leave a small remainder, fall back to the last move
+        priors.last().map(|(a, _)| *a)
+    }
+}
+
+/**
+ * Encodes the board as input planes from the perspective of the side to move
+ */
+fn encode(b: &Board) -> Vec<f32> {
+    let mut input = vec![0.0_f32; INPUTS];
+    let (own, opp): (u8, u8) = if b.is_player_turn() { (1, 2) } else { (2, 1) };
+
+    for (i, tile) in b.squares().iter().enumerate() {
+        if *tile == own {
+            input[i] = 1.0;
+        } else if *tile == opp {
+            input[SQUARES + i] = 1.0;
+        }
+    }
+
+    for action in b.get_available_actions(false) {
+        let sq: usize = action.into();
+        input[2 * SQUARES + sq] = 1.0;
+    }
+
+    if b.is_player_turn() {
+        input[3 * SQUARES] = 1.0;
+    }
+
+    input
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("network weights: {}", msg))
+}
@@ -0,0 +1,62 @@
+use crate::Board;
+
+/**
+ * Counts the leaf nodes of the game tree depth moves deep, to check move generation against
+ * published perft numbers, e.g. 4, 12, 56, 244, 1396, 8200, 55092, 390216 for depths 1 to 8
+ * from the starting position
+ *
+ * A side without actions passes, which counts as a move, and a finished game is a leaf
+ * however deep it is.
+ */
+pub fn perft(b: &Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let children = children(b);
+    if children.is_empty() {
+        return 1;
+    }
+
+    children.iter().map(|(_, child)| perft(child, depth - 1)).sum()
+}
+
+/**
+ * Splits perft() by the moves of the side to move, to find which move's subtree a wrong
+ * count comes from
+ *
+ * @returns:    each root move (None for a pass) with its leaf count, empty if the game is over
+ */
+pub fn divide(b: &Board, depth: u32) -> Vec<(Option<u8>, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+
+    children(b).iter().map(|(mv, child)| (*mv, perft(child, depth - 1))).collect()
+}
+
+/**
+ * Returns the positions after each move of the side to move, a single pass if it has no
+ * actions, or nothing if neither side has any
+ */
+fn children(b: &Board) -> Vec<(Option<u8>, Board)> {
+    let actions = b.get_available_actions(false);
+
+    if actions.is_empty() {
+        let opponent_actions = if b.is_player_turn() { b.get_cpu_actions() } else { b.get_player_actions() };
+        if opponent_actions.is_empty() {
+            return Vec::new();
+        }
+
+        let mut passed = b.clone();
+        passed.pass();
+        return vec![(None, passed)];
+    }
+
+    let val: u8 = if b.is_player_turn() { 1 } else { 2 };
+    actions.iter().map(|action| {
+        let mut child = b.clone();
+        child.ins(*action, val, false);
+        (Some(*action), child)
+    }).collect()
+}
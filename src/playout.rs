@@ -0,0 +1,284 @@
+use rand::Rng;
+
+use crate::nn::Network;
+use crate::{get_max_tile, Board, WEIGHTS};
+
+/**
+ * Chooses the moves made during a playout
+ *
+ * Policies are used for either colour, and always choose for the side to move.
+ */
+pub trait PlayoutPolicy {
+
+    /**
+     * Name used to select the policy from the command line
+     */
+    fn name(&self) -> String;
+
+    /**
+     * Returns the move to play for the side to move, or None if it has no actions
+     */
+    fn choose(&self, b: &Board) -> Option<u8>;
+}
+
+/**
+ * The policies used by each colour during a playout
+ */
+pub struct PlayoutPolicies<'a> {
+    pub player: &'a dyn PlayoutPolicy,
+    pub cpu: &'a dyn PlayoutPolicy
+}
+
+impl<'a> PlayoutPolicies<'a> {
+
+    /**
+     * Returns the policy of the player with value val (1 or 2)
+     */
+    pub fn for_side(&self, val: u8) -> &'a dyn PlayoutPolicy {
+        if val == 1 { self.player } else { self.cpu }
+    }
+}
+
+/**
+ * Names accepted by policy_from_name(), parameters are given after a colon
+ */
+pub const POLICY_NAMES: &str = "uniform, maxtile, epsilon-greedy[:epsilon], corner-first, mobility, softmax[:temperature]";
+
+/**
+ * Creates a playout policy from its name, e.g. "uniform" or "epsilon-greedy:0.2"
+ */
+pub fn policy_from_name(name: &str) -> Result<Box<dyn PlayoutPolicy>, String> {
+    let mut parts = name.splitn(2, ':');
+    let base = parts.next().unwrap_or("");
+    let param: Option<f32> = match parts.next() {
+        Some(p) => Some(p.parse().map_err(|_| format!("invalid parameter for playout policy {}: {}", base, p))?),
+        None => None
+    };
+
+    match base {
+        "uniform" => Ok(Box::new(Uniform)),
+        "maxtile" => Ok(Box::new(MaxTile)),
+        "epsilon-greedy" => match param.unwrap_or(0.1) {
+            epsilon if (0.0..=1.0).contains(&epsilon) => Ok(Box::new(EpsilonGreedy { epsilon })),
+            epsilon => Err(format!("epsilon of playout policy epsilon-greedy must be between 0 and 1, got {}", epsilon))
+        },
+        "corner-first" => Ok(Box::new(CornerFirst)),
+        "mobility" => Ok(Box::new(MobilityMin)),
+        "softmax" => match param.unwrap_or(10.0) {
+            temperature if temperature > 0.0 && temperature.is_finite() => Ok(Box::new(Softmax { temperature })),
+            temperature => Err(format!("temperature of playout policy softmax must be greater than 0, got {}", temperature))
+        },
+        _ => Err(format!("unknown playout policy '{}', expected one of: {}", name, POLICY_NAMES))
+    }
+}
+
+/**
+ * Plays a uniformly random available action
+ */
+pub struct Uniform;
+
+impl PlayoutPolicy for Uniform {
+
+    fn name(&self) -> String {
+        String::from("uniform")
+    }
+
+    fn choose(&self, b: &Board) -> Option<u8> {
+        random_action(b)
+    }
+}
+
+/**
+ * Plays the action that results in the highest score (Max Tile Heuristic)
+ */
+pub struct MaxTile;
+
+impl PlayoutPolicy for MaxTile {
+
+    fn name(&self) -> String {
+        String::from("maxtile")
+    }
+
+    fn choose(&self, b: &Board) -> Option<u8> {
+        match get_max_tile(b, false) {
+            99 => None,
+            pos => Some(pos)
+        }
+    }
+}
+
+/**
+ * Plays the action on the best square of the weight table, or a random action
+ * with probability epsilon
+ */
+pub struct EpsilonGreedy {
+    pub epsilon: f32
+}
+
+impl PlayoutPolicy for EpsilonGreedy {
+
+    fn name(&self) -> String {
+        format!("epsilon-greedy:{}", self.epsilon)
+    }
+
+    fn choose(&self, b: &Board) -> Option<u8> {
+        if rand::thread_rng().gen::<f32>() < self.epsilon {
+            return random_action(b);
+        }
+
+        b.get_available_actions(false).into_iter().max_by_key(|&a| {
+            let a_usize: usize = a.into();
+            WEIGHTS[a_usize]
+        })
+    }
+}
+
+/**
+ * Takes a corner whenever possible, otherwise plays randomly while avoiding the
+ * squares next to an empty corner
+ */
+pub struct CornerFirst;
+
+impl PlayoutPolicy for CornerFirst {
+
+    fn name(&self) -> String {
+        String::from("corner-first")
+    }
+
+    fn choose(&self, b: &Board) -> Option<u8> {
+        const CORNERS: [u8; 4] = [0, 7, 56, 63];
+
+        let actions = b.get_available_actions(false);
+        if let Some(corner) = actions.iter().find(|a| CORNERS.contains(a)) {
+            return Some(*corner);
+        }
+
+        let safe: Vec<u8> = actions.iter().cloned().filter(|&a| {
+            !CORNERS.iter().any(|&c| {
+                let c_usize: usize = c.into();
+                b.squares()[c_usize] == 0 && is_adjacent(a, c)
+            })
+        }).collect();
+
+        if safe.is_empty() {
+            random_action(b)
+        } else {
+            Some(safe[rand::thread_rng().gen_range(0, safe.len())])
+        }
+    }
+}
+
+/**
+ * Plays the action that leaves the opponent with the fewest available actions
+ */
+pub struct MobilityMin;
+
+impl PlayoutPolicy for MobilityMin {
+
+    fn name(&self) -> String {
+        String::from("mobility")
+    }
+
+    fn choose(&self, b: &Board) -> Option<u8> {
+        let val: u8 = side_to_move(b);
+
+        b.get_available_actions(false).into_iter().min_by_key(|&a| {
+            let mut new_board = b.clone();
+            new_board.ins(a, val, false);
+            new_board.get_available_actions(false).len()
+        })
+    }
+}
+
+/**
+ * Samples an action with probability proportional to exp(evaluation / temperature),
+ * where the evaluation is the weight table score of the resulting board for the mover
+ */
+pub struct Softmax {
+    pub temperature: f32
+}
+
+impl PlayoutPolicy for Softmax {
+
+    fn name(&self) -> String {
+        format!("softmax:{}", self.temperature)
+    }
+
+    fn choose(&self, b: &Board) -> Option<u8> {
+        let val: u8 = side_to_move(b);
+        let actions = b.get_available_actions(false);
+
+        let evals: Vec<(u8, f32)> = actions.iter().map(|&a| {
+            let mut new_board = b.clone();
+            new_board.ins(a, val, false);
+            (a, weighted_score(&new_board, val) as f32)
+        }).collect();
+
+        let max_eval = evals.iter().map(|(_, e)| *e).fold(f32::NEG_INFINITY, f32::max);
+        let exps: Vec<(u8, f32)> = evals.iter().map(|(a, e)| (*a, ((e - max_eval) / self.temperature).exp())).collect();
+        let total: f32 = exps.iter().map(|(_, e)| e).sum();
+
+        let mut r: f32 = rand::thread_rng().gen::<f32>() * total;
+        for (action, e) in exps.iter() {
+            if r < *e {
+                return Some(*action);
+            }
+            r -= e;
+        }
+
+        exps.last().map(|(a, _)| *a)
+    }
+}
+
+/**
+ * Samples CPU moves from the network's policy priors
+ */
+impl PlayoutPolicy for Network {
+
+    fn name(&self) -> String {
+        String::from("network")
+    }
+
+    fn choose(&self, b: &Board) -> Option<u8> {
+        self.sample_move(b)
+    }
+}
+
+fn random_action(b: &Board) -> Option<u8> {
+    let actions = b.get_available_actions(false);
+    if actions.is_empty() {
+        return None;
+    }
+
+    let rand_index = rand::thread_rng().gen_range(0, actions.len());
+    actions.get_index(rand_index).cloned()
+}
+
+fn side_to_move(b: &Board) -> u8 {
+    if b.is_player_turn() { 1 } else { 2 }
+}
+
+/**
+ * Sum of the weight table over the discs of val minus the discs of the opponent
+ */
+fn weighted_score(b: &Board, val: u8) -> i32 {
+    b.squares().iter().zip(WEIGHTS.iter()).map(|(tile, w)| {
+        if *tile == val {
+            *w
+        } else if *tile != 0 {
+            -w
+        } else {
+            0
+        }
+    }).sum()
+}
+
+/**
+ * Whether two squares touch horizontally, vertically or diagonally
+ */
+fn is_adjacent(a: u8, b: u8) -> bool {
+    let (ar, ac) = ((a / 8) as i8, (a % 8) as i8);
+    let (br, bc) = ((b / 8) as i8, (b % 8) as i8);
+
+    a != b && (ar - br).abs() <= 1 && (ac - bc).abs() <= 1
+}
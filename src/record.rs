@@ -0,0 +1,162 @@
+use std::fmt;
+use std::fs;
+use std::io;
+
+use crate::{convert_num, parse_pos, Board};
+
+/**
+ * Record of a game as the moves played from the starting position, or from a position set up
+ * with GameRecord::from_position()
+ *
+ * Each move is a board position, or None for a pass. Records are saved as text with the
+ * moves in the game's notation separated by whitespace, "PA" for a pass, e.g.
+ *      D3 C5 F6 PA E3
+ * Lines starting with # are comments. Games from a set up position start with a line holding
+ * the position in the format of Board::to_obf(), e.g.
+ *      position ---------------------------OX------XXX-------------------------- O
+ */
+#[derive(Clone, Default)]
+pub struct GameRecord {
+    start: Option<Board>,
+    moves: Vec<Option<u8>>
+}
+
+impl GameRecord {
+
+    pub fn new() -> GameRecord {
+        GameRecord { start: None, moves: Vec::new() }
+    }
+
+    /**
+     * Starts a record of a game played from the position on board
+     */
+    pub fn from_position(board: &Board) -> GameRecord {
+        GameRecord { start: Some(board.clone()), moves: Vec::new() }
+    }
+
+    /**
+     * Returns the position the game started from
+     */
+    pub fn start(&self) -> Board {
+        self.start.clone().unwrap_or_else(|| Board::new(8, 8))
+    }
+
+    /**
+     * Returns the moves played so far, None being a pass
+     */
+    pub fn moves(&self) -> &[Option<u8>] {
+        &self.moves
+    }
+
+    /**
+     * Plays pos for the side to move on board and records it
+     *
+     * @returns:    an error, leaving board unchanged, if pos is not an available action
+     */
+    pub fn play(&mut self, board: &mut Board, pos: u8) -> Result<(), String> {
+        if !board.get_available_actions(false).contains(&pos) {
+            return Err(format!("illegal move {}", convert_num(pos)));
+        }
+
+        let val: u8 = if board.is_player_turn() { 1 } else { 2 };
+        board.ins(pos, val, false);
+        self.moves.push(Some(pos));
+        Ok(())
+    }
+
+    /**
+     * Passes for the side to move on board and records it, only legal without actions
+     */
+    pub fn pass(&mut self, board: &mut Board) -> Result<(), String> {
+        if !board.get_available_actions(false).is_empty() {
+            return Err(String::from("illegal pass, the side to move has actions"));
+        }
+
+        board.pass();
+        self.moves.push(None);
+        Ok(())
+    }
+
+    /**
+     * Returns the position after the first n moves, or after every move if n is larger
+     */
+    pub fn board_at(&self, n: usize) -> Board {
+        let mut board = self.start();
+
+        for mv in self.moves.iter().take(n) {
+            match mv {
+                Some(pos) => {
+                    let val: u8 = if board.is_player_turn() { 1 } else { 2 };
+                    board.ins(*pos, val, false);
+                },
+                None => board.pass()
+            }
+        }
+
+        board
+    }
+
+    /**
+     * Returns the current position of the game
+     */
+    pub fn board(&self) -> Board {
+        self.board_at(self.moves.len())
+    }
+
+    /**
+     * Parses a record from text, checking every move is legal
+     */
+    pub fn parse(s: &str) -> Result<GameRecord, String> {
+        let mut record = GameRecord::new();
+        let mut lines = s.lines().filter(|l| !l.trim_start().starts_with('#')).peekable();
+
+        if let Some(position) = lines.peek().and_then(|l| l.trim_start().strip_prefix("position ")) {
+            record.start = Some(Board::from_obf(position)?);
+            lines.next();
+        }
+        let mut board = record.start();
+
+        for word in lines.flat_map(|l| l.split_whitespace()) {
+            if word.eq_ignore_ascii_case("PA") {
+                record.pass(&mut board)?;
+            } else {
+                match parse_pos(word) {
+                    Some(pos) => record.play(&mut board, pos)?,
+                    None => return Err(format!("invalid move {}", word))
+                }
+            }
+        }
+
+        Ok(record)
+    }
+
+    /**
+     * Writes the record to a file
+     */
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        fs::write(path, format!("{}\n", self))
+    }
+
+    /**
+     * Reads a record saved with GameRecord::save()
+     */
+    pub fn load(path: &str) -> io::Result<GameRecord> {
+        let text = fs::read_to_string(path)?;
+        GameRecord::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl fmt::Display for GameRecord {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let moves: Vec<String> = self.moves.iter().map(|mv| match mv {
+            Some(pos) => convert_num(*pos),
+            None => String::from("PA")
+        }).collect();
+
+        if let Some(start) = &self.start {
+            writeln!(f, "position {}", start.to_obf())?;
+        }
+        write!(f, "{}", moves.join(" "))
+    }
+}
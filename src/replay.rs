@@ -0,0 +1,125 @@
+use std::io;
+
+use reversi::clock::MoveBudget;
+use reversi::theme;
+use reversi::{convert_num, flipped, Engine, GameRecord};
+
+use crate::print_analysis;
+
+fn print_replay_help() {
+    let theme = theme::current();
+
+    println!("\nReplay commands:\n");
+    println!("  {}  -  show the next move (or press Enter)", theme.bold("next, n"));
+    println!("  {}  -  go back a move", theme.bold("prev, p"));
+    println!("  {}  -  jump to the position after that many moves, 0 being the start", theme.bold("<number>"));
+    println!("  {}  -  jump to the start or the end of the game", theme.bold("first, last"));
+    println!("  {}  -  search the position and print every available action's statistics", theme.bold("analyze"));
+    println!("                 ('analyze board' also ranks them on the board)");
+    println!("  {}     -  stop replaying", theme.bold("exit"));
+    println!();
+}
+
+/**
+ * Steps through the game saved at path, showing each move and the discs it flipped, and
+ * analyses any of its positions on request
+ *
+ *  Parameters:
+ *      engine  -   the engine used for analysis
+ *      secs    -   time each analysis searches for
+ */
+pub fn run(path: &str, engine: &mut dyn Engine, secs: u64) {
+    let record = match GameRecord::load(path) {
+        Ok(r) => r,
+        Err(e) => {
+            println!("ERROR: could not load {}: {}", path, e);
+            return;
+        }
+    };
+
+    let total = record.moves().len();
+    let mut ply: usize = 0;
+
+    println!("Replaying {} ({} moves), enter 'help' for the commands", path, total);
+    show(&record, ply);
+
+    loop {
+        println!("Replay ({}/{}): ", ply, total);
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => ()
+        }
+
+        let command = input.trim();
+        ply = match command {
+            "" | "n" | "next" => {
+                if ply == total {
+                    println!("End of the game");
+                    continue;
+                }
+                ply + 1
+            },
+            "p" | "prev" => {
+                if ply == 0 {
+                    println!("Start of the game");
+                    continue;
+                }
+                ply - 1
+            },
+            "first" => 0,
+            "last" => total,
+            "analyze" | "analyze board" => {
+                let board = record.board_at(ply);
+                engine.set_position(&board);
+                match engine.choose_move(&MoveBudget::fixed(secs)).and(engine.statistics()) {
+                    Some(result) => print_analysis(&board, result, command == "analyze board"),
+                    None => println!("The side to move has no actions")
+                }
+                continue;
+            },
+            "help" => {
+                print_replay_help();
+                continue;
+            },
+            "exit" | "quit" => return,
+            _ => match command.parse::<usize>() {
+                Ok(n) if n <= total => n,
+                Ok(_) => {
+                    println!("ERROR: the game only has {} moves", total);
+                    continue;
+                },
+                Err(_) => {
+                    println!("ERROR: Invalid entry, enter 'help' for the commands");
+                    continue;
+                }
+            }
+        };
+
+        show(&record, ply);
+    }
+}
+
+/**
+ * Prints the position after the first ply moves and the move that led to it
+ */
+fn show(record: &GameRecord, ply: usize) {
+    let board = record.board_at(ply);
+    board.print();
+
+    if ply == 0 {
+        println!("Starting position\n");
+        return;
+    }
+
+    let before = record.board_at(ply - 1);
+    let side = if before.is_player_turn() { "Player" } else { "CPU" };
+
+    match record.moves()[ply - 1] {
+        Some(pos) => {
+            let flips: Vec<String> = flipped(&before, &board).into_iter().map(convert_num).collect();
+            println!("Move {}: {} played {}, flipping {}\n", ply, side, convert_num(pos), flips.join(" "));
+        },
+        None => println!("Move {}: {} passed\n", ply, side)
+    }
+}
@@ -0,0 +1,478 @@
+use std::io::stderr;
+use std::io::Write;
+use rand::Rng;
+
+// HashMap is used in order to assign a count to each element inside win/draw/loss stats
+use std::collections::HashMap;
+
+use indexmap::IndexSet;
+
+// Used to limit MCTS duration
+use std::time::{Duration, Instant};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::clock::{top_two, MoveBudget};
+use crate::nn::Network;
+use crate::playout::PlayoutPolicies;
+use crate::tree::{puct_search, PuctConfig};
+use crate::Board;
+
+/**
+ * Upper limits on the work done in one search, the move budget normally stops it first
+ */
+pub const MAX_STEPS: usize = 1000;
+pub const MAX_SIMULATIONS: usize = 100000;
+
+/**
+ * Search statistics for one of the available actions
+ *
+ *      visits      -   number of playouts or simulations that started with the action
+ *      value       -   mean result for the side to move, counting a win as 1 and a draw as 0.5
+ *      wins        -   playouts that started with the action and ended in a win, draw or
+ *      draws           loss for the side to move. Tree simulations that stop at a network
+ *      losses          evaluation do not reach the end of the game and are not counted.
+ *      disc_diff   -   mean final disc differential for the side to move over those playouts
+ */
+pub struct MoveStats {
+    pub action: u8,
+    pub visits: u32,
+    pub value: f32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub disc_diff: f32
+}
+
+impl MoveStats {
+
+    /**
+     * Number of playouts that reached the end of the game
+     */
+    pub fn finished(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+}
+
+/**
+ * Results of the finished playouts that started with one action
+ */
+#[derive(Default, Clone, Copy)]
+pub(crate) struct Outcomes {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    disc_sum: i32
+}
+
+impl Outcomes {
+
+    /**
+     * Records the final position b of a playout for the side me
+     */
+    pub(crate) fn record(&mut self, b: &Board, me: u8) {
+        let (player_score, cpu_score) = b.get_score();
+        let diff = if me == 1 { player_score as i32 - cpu_score as i32 } else { cpu_score as i32 - player_score as i32 };
+
+        if diff > 0 {
+            self.wins += 1;
+        } else if diff < 0 {
+            self.losses += 1;
+        } else {
+            self.draws += 1;
+        }
+        self.disc_sum += diff;
+    }
+
+    /**
+     * Statistics of action given its visits and value
+     */
+    pub(crate) fn stats(&self, action: u8, visits: u32, value: f32) -> MoveStats {
+        let finished = self.wins + self.draws + self.losses;
+        let disc_diff = if finished == 0 { 0.0 } else { self.disc_sum as f32 / finished as f32 };
+
+        MoveStats { action, visits, value, wins: self.wins, draws: self.draws, losses: self.losses, disc_diff }
+    }
+}
+
+/**
+ * Result of a search: the move to play and the statistics of every available action,
+ * sorted best first
+ *
+ *      pv          -   principal variation, the expected best line starting with best
+ *      playouts    -   playouts or simulations searched
+ *      elapsed     -   time the search took
+ */
+pub struct SearchResult {
+    pub best: u8,
+    pub moves: Vec<MoveStats>,
+    pub pv: Vec<u8>,
+    pub playouts: usize,
+    pub elapsed: Duration
+}
+
+impl SearchResult {
+
+    /**
+     * Returns the statistics of the best move
+     */
+    pub fn best_stats(&self) -> Option<&MoveStats> {
+        self.moves.iter().find(|m| m.action == self.best)
+    }
+
+    pub fn playouts_per_second(&self) -> f64 {
+        if self.elapsed.as_secs_f64() > 0.0 {
+            self.playouts as f64 / self.elapsed.as_secs_f64()
+        } else {
+            0.0
+        }
+    }
+}
+
+/**
+ * Number of moves of the flat search's playouts kept for its principal variation
+ */
+const PV_DEPTH: usize = 8;
+
+/**
+ * Fewest playouts through a reply for the flat search's principal variation to follow it
+ */
+const PV_MIN_VISITS: u32 = 5;
+
+/**
+ * Tree of the first PV_DEPTH moves of the flat search's playouts
+ *
+ * value_sum is stored from the perspective of `side`, the player who played the move
+ * leading to this node.
+ */
+#[derive(Default)]
+struct LineNode {
+    side: u8,
+    visits: u32,
+    value_sum: f32,
+    children: HashMap<u8, LineNode>
+}
+
+impl LineNode {
+
+    /**
+     * Adds a playout's moves with its value for the side me
+     */
+    fn add(&mut self, moves: &[(u8, u8)], value: f32, me: u8) {
+        if let Some(((side, pos), rest)) = moves.split_first() {
+            let child = self.children.entry(*pos).or_default();
+            child.side = *side;
+            child.visits += 1;
+            child.value_sum += if *side == me { value } else { 1.0 - value };
+            child.add(rest, value, me);
+        }
+    }
+
+    /**
+     * Follows best, then the reply with the best mean value for the side playing it among
+     * those played at least PV_MIN_VISITS times
+     */
+    fn principal_variation(&self, best: u8) -> Vec<u8> {
+        let mut pv = vec![best];
+        let mut node = match self.children.get(&best) {
+            Some(n) => n,
+            None => return pv
+        };
+
+        while let Some((pos, child)) = node.children.iter()
+            .filter(|(_, c)| c.visits >= PV_MIN_VISITS)
+            .max_by(|a, b| (a.1.value_sum / a.1.visits as f32).partial_cmp(&(b.1.value_sum / b.1.visits as f32)).unwrap()) {
+            pv.push(*pos);
+            node = child;
+        }
+
+        pv
+    }
+}
+
+/**
+ * Snapshot of a running search, reported to Searcher::progress
+ *
+ *      playouts    -   playouts or simulations done so far
+ *      best        -   the currently preferred action
+ *      win_rate    -   mean result of the preferred action for the side to move
+ */
+pub struct SearchProgress {
+    pub playouts: usize,
+    pub best: u8,
+    pub win_rate: f32
+}
+
+/**
+ * The CPU's search settings, shared by the game loop and the protocol front-ends
+ *
+ *      policies    -   playout policies of each player
+ *      network     -   optional value/policy network for the PUCT search
+ *      puct        -   PUCT settings, or None for the flat Monte Carlo search
+ *      progress    -   optional callback given search statistics while the search runs
+ *      stop        -   optional flag that ends the search early once set
+ */
+pub struct Searcher<'a> {
+    pub policies: PlayoutPolicies<'a>,
+    pub network: Option<&'a Network>,
+    pub puct: Option<PuctConfig>,
+    pub progress: Option<&'a dyn Fn(&SearchProgress)>,
+    pub stop: Option<&'a AtomicBool>
+}
+
+impl<'a> Searcher<'a> {
+
+    /**
+     * Searches the board for the side to move within the budget
+     *
+     * @returns:    None if the side to move has no actions
+     */
+    pub fn search(&self, b: &Board, budget: &MoveBudget, debug: bool) -> Option<SearchResult> {
+        match &self.puct {
+            Some(config) => puct_search(b, MAX_SIMULATIONS, budget, self, config, debug),
+            None => monte_carlo_tree_search(b, MAX_STEPS, budget, self, debug)
+        }
+    }
+
+    /**
+     * Returns true once the stop flag has been set
+     */
+    pub fn stopped(&self) -> bool {
+        self.stop.is_some_and(|s| s.load(Ordering::Relaxed))
+    }
+}
+
+/**
+ * Simplified Monte Carlo Tree Search which performs random playouts until completion 
+ * and records the win/draw/loss statistics for each available action at current board state.
+ *  Parameters:
+ *      b              -    the current board state to initialize the playout board, searched
+ *                          for the side to move
+ *      max_steps      -    maximum number of iterations 
+ *      budget         -    time budget of the search, see MoveBudget::should_stop()
+ *      searcher       -    playout policies used for the moves of each player and the
+ *                          progress callback
+ *      debug          -    used to print extra debug statements
+ * 
+ * @returns:    None if the side to move has no actions
+ */
+pub fn monte_carlo_tree_search(b: &Board, max_steps: usize, budget: &MoveBudget, searcher: &Searcher, debug: bool) -> Option<SearchResult> {
+
+    let mut stats: [Vec<u8>; 3] = [vec![], vec![], vec![]];
+    let start_time = Instant::now();
+    let me: u8 = if b.is_player_turn() { 1 } else { 2 };
+
+    // Nothing to search if there are no options or only one
+    let root_actions = b.get_available_actions(debug);
+    if root_actions.is_empty() {
+        return None;
+    }
+    if root_actions.len() == 1 {
+        let action = *root_actions.get_index(0).unwrap();
+        return Some(SearchResult {
+            best: action,
+            moves: vec![Outcomes::default().stats(action, 0, 0.5)],
+            pv: vec![action],
+            playouts: 0,
+            elapsed: start_time.elapsed()
+        });
+    }
+    
+    let policies = &searcher.policies;
+    if debug {
+        println!("Searching {} random playouts (Player: {}, CPU: {})...",
+            max_steps, policies.player.name(), policies.cpu.name());
+    }
+
+    // Populate hashmap with frequency of elements in win list
+    let mut a: HashMap<u8, usize> = HashMap::new();
+    let mut outcomes: HashMap<u8, Outcomes> = HashMap::new();
+    let mut lines = LineNode::default();
+    
+    for i in 0..max_steps {
+
+        // Progress goes to stderr so protocol front-ends keep stdout to themselves,
+        // a progress callback replaces it
+        if searcher.progress.is_none() {
+            if !debug { eprint!("."); stderr().flush().unwrap(); }
+            if (i + 1) % 30 == 0 { eprintln!() }
+        }
+
+        // Break out of function when the budget is used or the best play can't be overtaken
+        let (best, second) = top_two(a.values().cloned());
+        if let (Some(progress), Some((&action, _))) = (searcher.progress, a.iter().max_by(|a, b| a.1.cmp(b.1))) {
+            let moves = flat_stats(&root_actions, &outcomes);
+            let win_rate = moves.iter().find(|m| m.action == action).map_or(0.5, |m| m.value);
+            progress(&SearchProgress { playouts: stats.iter().map(|s| s.len()).sum(), best: action, win_rate });
+        }
+        if budget.should_stop(start_time.elapsed(), i, best, second) || searcher.stopped() { 
+            let res: f64 = i as f64 / start_time.elapsed().as_secs_f64();
+            if debug { println!("Play-outs per second: {:.0}", res); }
+            break;
+        }
+        
+        let actions = b.get_available_actions(debug);
+
+        if debug { println!("Step #{} | {:?}", i, actions); }
+        
+        for action in actions {
+
+            if debug { println!("Playing action: {}", action); }
+
+            // The playout continues from the position after the action
+            let mut playout_board: Board = b.clone();
+            playout_board.ins(action, me, debug);
+            let mut played: Vec<(u8, u8)> = vec![(me, action)];
+
+            let winner = random_playout(&mut playout_board, policies, Some(&mut played), debug);
+            match winner {
+                3 => stats[2].push(action), // 3 -> Game draw so add action to draw list
+                winner if winner == me => { // Side to move wins so add action to win list
+                    stats[0].push(action);
+                    *a.entry(action).or_insert(0) += 1;
+                },
+                1 | 2 => stats[1].push(action), // Opponent wins so add action to loss list
+                _ => continue
+            };
+            outcomes.entry(action).or_default().record(&playout_board, me);
+
+            let value = if winner == me { 1.0 } else if winner == 3 { 0.5 } else { 0.0 };
+            lines.add(&played[..played.len().min(PV_DEPTH)], value, me);
+        }
+    }
+
+    if debug {
+        println!("Losses: {:?}", stats[1]);
+        println!("Wins: {:?}", stats[0]);
+        println!("Draws: {:?}", stats[2]);
+        for (pos, wins) in &a {
+            println!("{}: {}", pos, wins);
+        } 
+    }
+
+    // Returns the highest value in frequency hashmap as best play if win list exists,
+    // else return a random action if no elements exist in win list.
+    let best: u8 = if stats[0].is_empty() {
+        let actions_size = root_actions.len();
+        let rand_index = rand::thread_rng().gen_range(0, actions_size);
+        let rand_val = root_actions.get_index(rand_index).unwrap();
+        *rand_val
+    }
+    
+    else {
+        *a.iter().max_by(|a, b| a.1.cmp(b.1)).map(|(k, _v)| k).unwrap()
+    };
+
+    let mut moves = flat_stats(&root_actions, &outcomes);
+    moves.sort_by(|x, y| y.value.partial_cmp(&x.value).unwrap());
+
+    Some(SearchResult {
+        best,
+        playouts: moves.iter().map(|m| m.visits as usize).sum(),
+        moves,
+        pv: lines.principal_variation(best),
+        elapsed: start_time.elapsed()
+    })
+}
+
+/**
+ * Statistics of each root action from the flat search's playout outcomes
+ */
+fn flat_stats(root_actions: &IndexSet<u8>, outcomes: &HashMap<u8, Outcomes>) -> Vec<MoveStats> {
+    root_actions.iter().map(|&action| {
+        let o = outcomes.get(&action).cloned().unwrap_or_default();
+        let visits = o.wins + o.draws + o.losses;
+        let value = if visits == 0 { 0.5 } else { (o.wins as f32 + 0.5 * o.draws as f32) / visits as f32 };
+        o.stats(action, visits, value)
+    }).collect()
+}
+
+
+/**
+ * Plays a game until completion from b, each player choosing its moves with its playout policy.
+ *
+ * If played is given, every (player, position) played during the playout is appended to it.
+ */
+pub fn random_playout(b: &mut Board, policies: &PlayoutPolicies, mut played: Option<&mut Vec<(u8, u8)>>, debug: bool) -> u8 {
+
+    // Play a game until completion
+    loop {
+        match b.check_game_state(debug) {
+            0 => { // Game not done
+                let val: u8 = if b.is_player_turn() { 1 } else { 2 };
+
+                let new_val: u8 = match policies.for_side(val).choose(b) {
+                    Some(x) => x,
+                    None => {
+                        println!("ERROR in random_playout() -> {} found no action", policies.for_side(val).name());
+                        return 42;
+                    }
+                };
+
+                b.ins(new_val, val, debug);
+                if let Some(moves) = played.as_deref_mut() {
+                    moves.push((val, new_val));
+                }
+
+                if debug { b.print(); }
+                continue;
+            },
+            1 => return 1, // Player Wins
+            2 => return 2, // CPU Wins
+            3 => return 3, // Draw
+            _ => return 42
+        };
+    }
+}
+
+/**
+ * Positional Weight Table Heuristic
+ *      - Static value of owning each square, corners are the most valuable and the
+ *        squares next to the corners the least since they give the corner away
+ */
+pub const WEIGHTS: [i32; 64] = [
+    100, -20,  10,   5,   5,  10, -20, 100,
+    -20, -50,  -2,  -2,  -2,  -2, -50, -20,
+     10,  -2,  -1,  -1,  -1,  -1,  -2,  10,
+      5,  -2,  -1,  -1,  -1,  -1,  -2,   5,
+      5,  -2,  -1,  -1,  -1,  -1,  -2,   5,
+     10,  -2,  -1,  -1,  -1,  -1,  -2,  10,
+    -20, -50,  -2,  -2,  -2,  -2, -50, -20,
+    100, -20,  10,   5,   5,  10, -20, 100
+];
+
+/**
+ * Max Tile Heuristic
+ *      - Returns the position that results in the highest score for the side to move
+ *      - If no actions are available, then return an error code of 99 to indicate game end                      
+ */
+pub fn get_max_tile(b: &Board, debug: bool) -> u8 {
+
+    let actions = b.get_available_actions(debug);
+    let val: u8 = if b.is_player_turn() { 1 } else { 2 };
+    let mut best_score: u8 = 0;
+    let mut best_pos: u8 = 0;
+    
+    if debug { println!("{:?}", actions); }
+
+    if actions.is_empty() {
+        return 99;
+    }
+
+    for action in actions {
+        // check increase in value of tiles
+        let mut new_board: Board = b.clone();
+        
+        new_board.ins(action, val, debug);
+
+        let (player_score, cpu_score): (u8, u8) = new_board.get_score();
+        let score = if val == 1 { player_score } else { cpu_score };
+
+        if score > best_score {
+            best_score = score;
+            best_pos = action;
+        }
+    }
+    
+    best_pos
+}
@@ -0,0 +1,219 @@
+use std::cell::Cell;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tungstenite::{accept, connect, Message, WebSocket};
+
+use reversi::{convert_num, Board, SearchProgress};
+
+/**
+ * Least time between two search updates, so spectators are not flooded by fast searches
+ */
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/**
+ * Longest a spectator may take to accept a message before it is dropped, so a stalled
+ * spectator can't hold up the game
+ */
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/**
+ * Longest a new spectator may take to complete the WebSocket handshake, so a connection that
+ * never sends one can't keep others from joining
+ */
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/**
+ * Pushes a game to WebSocket spectators as JSON text messages
+ *
+ * Message types:
+ *      board       -   sent on connect and after each move: the move and side that played it
+ *                      (null before the first move), squares, score, side to move and result
+ *      search      -   live statistics of the running search: playouts, best move, win rate
+ *
+ * Spectators that join mid-game are sent the latest board first. Search statistics are only
+ * sent between follow_search(true) and follow_search(false), so that hints and analysis
+ * for the Player are not streamed as the CPU's search.
+ */
+pub struct Broadcaster {
+    clients: Arc<Mutex<Vec<WebSocket<TcpStream>>>>,
+    latest: Arc<Mutex<String>>,
+    last_progress: Cell<Option<Instant>>,
+    following: Cell<bool>
+}
+
+impl Broadcaster {
+
+    /**
+     * Listens for spectators on addr (e.g. 127.0.0.1:9001)
+     */
+    pub fn start(addr: &str, b: &Board) -> io::Result<Broadcaster> {
+        let listener = TcpListener::bind(addr)?;
+        let broadcaster = Broadcaster {
+            clients: Arc::new(Mutex::new(Vec::new())),
+            latest: Arc::new(Mutex::new(board_message(b, None).to_string())),
+            last_progress: Cell::new(None),
+            following: Cell::new(false)
+        };
+
+        let clients = Arc::clone(&broadcaster.clients);
+        let latest = Arc::clone(&broadcaster.latest);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err()
+                    || stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).is_err() {
+                    continue;
+                }
+                let mut socket = match accept(stream) {
+                    Ok(s) => s,
+                    Err(_) => continue
+                };
+
+                // Holding the clients lock while sending the snapshot means a board sent after
+                // the snapshot was read waits for this spectator to be added
+                let mut clients = clients.lock().unwrap();
+                let snapshot = latest.lock().unwrap().clone();
+                if socket.send(Message::text(snapshot)).is_ok() {
+                    clients.push(socket);
+                }
+            }
+        });
+
+        Ok(broadcaster)
+    }
+
+    /**
+     * Sends the board after side played pos
+     */
+    pub fn board(&self, b: &Board, side: u8, pos: u8) {
+        let msg = board_message(b, Some((side, pos))).to_string();
+        *self.latest.lock().unwrap() = msg.clone();
+        self.send(msg);
+    }
+
+    /**
+     * Starts or stops sending the statistics of the searches that follow, set around the
+     * CPU's own move searches
+     */
+    pub fn follow_search(&self, follow: bool) {
+        self.following.set(follow);
+    }
+
+    /**
+     * Sends live search statistics while following the search, at most once every
+     * PROGRESS_INTERVAL
+     */
+    pub fn progress(&self, p: &SearchProgress) {
+        if !self.following.get() || self.last_progress.get().is_some_and(|t| t.elapsed() < PROGRESS_INTERVAL) {
+            return;
+        }
+        self.last_progress.set(Some(Instant::now()));
+
+        self.send(json!({
+            "type": "search",
+            "playouts": p.playouts,
+            "best": convert_num(p.best),
+            "win_rate": p.win_rate
+        }).to_string());
+    }
+
+    /**
+     * Sends a message to every spectator, dropping those that have disconnected or did not
+     * accept it within WRITE_TIMEOUT
+     */
+    fn send(&self, msg: String) {
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain_mut(|socket| socket.send(Message::text(msg.clone())).is_ok());
+    }
+}
+
+fn board_message(b: &Board, last: Option<(u8, u8)>) -> Value {
+    let (player, cpu) = b.get_score();
+
+    let state = match b.check_game_state(false) {
+        1 => "player_won",
+        2 => "cpu_won",
+        3 => "draw",
+        _ => "in_progress"
+    };
+
+    json!({
+        "type": "board",
+        "move": last.map(|(_, pos)| convert_num(pos)),
+        "side": last.map(|(side, _)| if side == 1 { "player" } else { "cpu" }),
+        "squares": b.squares(),
+        "score": { "player": player, "cpu": cpu },
+        "to_move": if b.is_player_turn() { "player" } else { "cpu" },
+        "state": state
+    })
+}
+
+/**
+ * Spectates a streamed game at addr (host:port), printing the board after each move and
+ * the search statistics as they arrive, until the stream closes
+ */
+pub fn watch(addr: &str) {
+    let (mut socket, _) = match connect(format!("ws://{}", addr)) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("ERROR: could not connect to {}: {}", addr, e);
+            return;
+        }
+    };
+
+    println!("Watching the game at {}", addr);
+
+    while let Ok(msg) = socket.read() {
+        let text = match msg {
+            Message::Text(t) => t,
+            Message::Close(_) => break,
+            _ => continue
+        };
+        let value: Value = match serde_json::from_str(&text) {
+            Ok(v) => v,
+            Err(_) => continue
+        };
+
+        match value["type"].as_str() {
+            Some("board") => show(&value),
+            Some("search") => println!("Searching: {} playouts, best {} ({:.1}%)",
+                value["playouts"], value["best"].as_str().unwrap_or("-"),
+                value["win_rate"].as_f64().unwrap_or(0.0) * 100.0),
+            _ => ()
+        }
+    }
+
+    println!("Stream closed");
+}
+
+/**
+ * Prints a board message as plain text: X is the Player, O is the CPU
+ */
+fn show(value: &Value) {
+    if let (Some(mv), Some(side)) = (value["move"].as_str(), value["side"].as_str()) {
+        println!("\n{} played {}", if side == "player" { "Player" } else { "CPU" }, mv);
+    }
+
+    let squares: Vec<u64> = value["squares"].as_array().map(|s| s.iter().filter_map(|v| v.as_u64()).collect()).unwrap_or_default();
+    println!("  A B C D E F G H");
+    for (row, line) in squares.chunks(8).enumerate() {
+        let tiles: Vec<&str> = line.iter().map(|t| match t {
+            1 => "X",
+            2 => "O",
+            _ => "."
+        }).collect();
+        println!("{} {}", row + 1, tiles.join(" "));
+    }
+
+    println!("Player (X): {}, CPU (O): {}, {}", value["score"]["player"], value["score"]["cpu"],
+        match value["state"].as_str() {
+            Some("player_won") => "Player has won",
+            Some("cpu_won") => "CPU has won",
+            Some("draw") => "Game is a draw",
+            _ => if value["to_move"] == "player" { "Player to move" } else { "CPU to move" }
+        });
+}
@@ -0,0 +1,164 @@
+use std::fmt::Write;
+use std::fs;
+use std::io;
+
+use crate::{Board, GameRecord};
+
+/**
+ * Colours of the rendered board
+ */
+const BOARD_COLOUR: &str = "#2e7d32";
+const LINE_COLOUR: &str = "#1b3d1d";
+const PLAYER_COLOUR: &str = "#111111";
+const CPU_COLOUR: &str = "#f5f5f5";
+const ACTION_COLOUR: &str = "#000000";
+const LAST_MOVE_COLOUR: &str = "#e53935";
+const TEXT_COLOUR: &str = "#333333";
+
+/**
+ * What is drawn on a rendered board
+ *
+ *      square      -   width and height of a square in pixels
+ *      coordinates -   letters and numbers around the board
+ *      actions     -   dots on the available actions of the side to move
+ *      last_move   -   square to mark as the last move played
+ */
+#[derive(Clone)]
+pub struct SvgOptions {
+    pub square: u32,
+    pub coordinates: bool,
+    pub actions: bool,
+    pub last_move: Option<u8>
+}
+
+impl Default for SvgOptions {
+
+    fn default() -> SvgOptions {
+        SvgOptions {
+            square: 48,
+            coordinates: true,
+            actions: true,
+            last_move: None
+        }
+    }
+}
+
+/**
+ * Renders a position as a standalone SVG image
+ *
+ * The Player's (1) discs are black and the CPU's (2) white, as in the library's notation.
+ */
+pub fn position_svg(b: &Board, options: &SvgOptions) -> String {
+    let (width, height) = b.size();
+    let sq = options.square;
+    let margin = if options.coordinates { sq / 2 } else { 0 };
+    let total_width = margin * 2 + sq * width as u32;
+    let total_height = margin * 2 + sq * height as u32;
+    let radius = sq as f32 * 0.42;
+
+    // Writing to a String can't fail, so the results of write! are ignored
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        total_width, total_height, total_width, total_height);
+    let _ = writeln!(svg, r#"<rect width="{}" height="{}" fill="white"/>"#, total_width, total_height);
+    let _ = writeln!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="{}" stroke-width="2"/>"#,
+        margin, margin, sq * width as u32, sq * height as u32, BOARD_COLOUR, LINE_COLOUR);
+
+    // Grid lines between the squares
+    for col in 1..width as u32 {
+        let x = margin + col * sq;
+        let _ = writeln!(svg, r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}"/>"#, x, margin, x, total_height - margin, LINE_COLOUR);
+    }
+    for row in 1..height as u32 {
+        let y = margin + row * sq;
+        let _ = writeln!(svg, r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}"/>"#, margin, y, total_width - margin, y, LINE_COLOUR);
+    }
+
+    if options.coordinates {
+        let font_size = sq / 3;
+        for col in 0..width as u32 {
+            let letter = (b'A' + col as u8) as char;
+            let x = margin + col * sq + sq / 2;
+            let _ = writeln!(svg, r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                x, margin / 2, font_size, TEXT_COLOUR, letter);
+        }
+        for row in 0..height as u32 {
+            let y = margin + row * sq + sq / 2;
+            let _ = writeln!(svg, r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                margin / 2, y, font_size, TEXT_COLOUR, row + 1);
+        }
+    }
+
+    let actions = if options.actions { b.get_available_actions(false) } else { Default::default() };
+
+    for (pos, square) in (0_u8..).zip(b.squares().iter()) {
+        let cx = margin + (pos % width) as u32 * sq + sq / 2;
+        let cy = margin + (pos / width) as u32 * sq + sq / 2;
+
+        match square {
+            1 | 2 => {
+                let fill = if *square == 1 { PLAYER_COLOUR } else { CPU_COLOUR };
+                let _ = writeln!(svg, r#"<circle cx="{}" cy="{}" r="{:.1}" fill="{}" stroke="{}" stroke-width="1"/>"#,
+                    cx, cy, radius, fill, LINE_COLOUR);
+            },
+            _ if actions.contains(&pos) => {
+                let _ = writeln!(svg, r#"<circle cx="{}" cy="{}" r="{:.1}" fill="{}" fill-opacity="0.35"/>"#,
+                    cx, cy, sq as f32 * 0.1, ACTION_COLOUR);
+            },
+            _ => ()
+        }
+
+        if options.last_move == Some(pos) {
+            let _ = writeln!(svg, r#"<circle cx="{}" cy="{}" r="{:.1}" fill="{}"/>"#, cx, cy, sq as f32 * 0.1, LAST_MOVE_COLOUR);
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/**
+ * Renders a game as one SVG frame per position, from the starting position to the final one,
+ * marking the move that led to each
+ *
+ * options.last_move is ignored.
+ */
+pub fn game_svgs(record: &GameRecord, options: &SvgOptions) -> Vec<String> {
+    let mut board = record.start();
+    let mut frame_options = SvgOptions { last_move: None, ..options.clone() };
+    let mut frames = vec![position_svg(&board, &frame_options)];
+
+    for mv in record.moves() {
+        match mv {
+            Some(pos) => {
+                let val: u8 = if board.is_player_turn() { 1 } else { 2 };
+                board.ins(*pos, val, false);
+            },
+            None => board.pass()
+        }
+        frame_options.last_move = *mv;
+        frames.push(position_svg(&board, &frame_options));
+    }
+
+    frames
+}
+
+/**
+ * Writes a game's frames to numbered files, e.g. game-000.svg, game-001.svg, ...
+ *
+ *  Parameters:
+ *      prefix  -   path of the files without the frame number and extension
+ *
+ *  @returns:   the paths written
+ */
+pub fn save_game_svgs(record: &GameRecord, prefix: &str, options: &SvgOptions) -> io::Result<Vec<String>> {
+    let mut paths = Vec::new();
+
+    for (n, frame) in game_svgs(record, options).iter().enumerate() {
+        let path = format!("{}-{:03}.svg", prefix, n);
+        fs::write(&path, frame)?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
@@ -0,0 +1,266 @@
+use std::env;
+use std::io::{stdout, IsTerminal};
+use std::sync::RwLock;
+
+use ansi_term::Colour;
+use ansi_term::Colour::{Fixed, Green, Red, Yellow};
+use ansi_term::Style;
+
+/**
+ * How boards and discs are drawn in the terminal
+ *
+ *      name            -   name used to select the theme, see THEME_NAMES
+ *      player, cpu     -   colour of each side's discs and score, None for the terminal's own
+ *      label           -   colour of square labels such as analysis ranks
+ *      player_name     -   what the rules call each side's discs, e.g. "Red"
+ *      player_disc     -   glyph of each side's discs
+ *      empty           -   glyph of an empty square
+ *      action          -   glyph marking the Player's available actions
+ *      hint            -   glyph marking a suggested move
+ *      colour          -   whether any colours or styles are used at all
+ */
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub name: &'static str,
+    pub player: Option<Colour>,
+    pub cpu: Option<Colour>,
+    pub label: Option<Colour>,
+    pub player_name: &'static str,
+    pub cpu_name: &'static str,
+    pub player_disc: char,
+    pub cpu_disc: char,
+    pub empty: char,
+    pub action: char,
+    pub hint: char,
+    pub colour: bool
+}
+
+/**
+ * Names accepted by Theme::from_name()
+ */
+pub const THEME_NAMES: &str = "red-green, classic, high-contrast, colourblind, tritan, ascii";
+
+/**
+ * The theme set with set_theme(), the default theme until then
+ */
+static CURRENT: RwLock<Option<Theme>> = RwLock::new(None);
+
+impl Theme {
+
+    /**
+     * The original red Player and green CPU discs
+     */
+    pub const RED_GREEN: Theme = Theme {
+        name: "red-green",
+        player: Some(Red),
+        cpu: Some(Green),
+        label: Some(Yellow),
+        player_name: "Red",
+        cpu_name: "Green",
+        player_disc: '●',
+        cpu_disc: '●',
+        empty: '-',
+        action: '*',
+        hint: '◆',
+        colour: true
+    };
+
+    /**
+     * Filled black and hollow white discs in the terminal's own colours
+     */
+    pub const CLASSIC: Theme = Theme {
+        name: "classic",
+        player: None,
+        cpu: None,
+        player_name: "Black (●)",
+        cpu_name: "White (○)",
+        player_disc: '●',
+        cpu_disc: '○',
+        ..Theme::RED_GREEN
+    };
+
+    /**
+     * Bright yellow and bright cyan discs with different glyphs, for low contrast displays
+     */
+    pub const HIGH_CONTRAST: Theme = Theme {
+        name: "high-contrast",
+        player: Some(Fixed(11)),
+        cpu: Some(Fixed(14)),
+        label: Some(Fixed(13)),
+        player_name: "Yellow (●)",
+        cpu_name: "Cyan (○)",
+        player_disc: '●',
+        cpu_disc: '○',
+        ..Theme::RED_GREEN
+    };
+
+    /**
+     * Blue and orange discs, safe for red-green (protanopia and deuteranopia) colour blindness
+     */
+    pub const COLOURBLIND: Theme = Theme {
+        name: "colourblind",
+        player: Some(Fixed(33)),
+        cpu: Some(Fixed(208)),
+        label: Some(Fixed(15)),
+        player_name: "Blue",
+        cpu_name: "Orange",
+        ..Theme::RED_GREEN
+    };
+
+    /**
+     * Red and cyan discs, safe for blue-yellow (tritanopia) colour blindness
+     */
+    pub const TRITAN: Theme = Theme {
+        name: "tritan",
+        player: Some(Fixed(160)),
+        cpu: Some(Fixed(37)),
+        player_name: "Red",
+        cpu_name: "Cyan",
+        ..Theme::RED_GREEN
+    };
+
+    /**
+     * X and O discs without colours or non-ASCII characters
+     */
+    pub const ASCII: Theme = Theme {
+        name: "ascii",
+        player: None,
+        cpu: None,
+        label: None,
+        player_name: "X",
+        cpu_name: "O",
+        player_disc: 'X',
+        cpu_disc: 'O',
+        empty: '.',
+        action: '*',
+        hint: '+',
+        colour: false
+    };
+
+    /**
+     * Returns the theme called name, e.g. "classic" or "colourblind"
+     */
+    pub fn from_name(name: &str) -> Result<Theme, String> {
+        match name {
+            "red-green" => Ok(Theme::RED_GREEN),
+            "classic" => Ok(Theme::CLASSIC),
+            "high-contrast" => Ok(Theme::HIGH_CONTRAST),
+            "colourblind" | "colorblind" => Ok(Theme::COLOURBLIND),
+            "tritan" => Ok(Theme::TRITAN),
+            "ascii" => Ok(Theme::ASCII),
+            _ => Err(format!("unknown theme '{}', expected one of: {}", name, THEME_NAMES))
+        }
+    }
+
+    /**
+     * The same theme without colours or styles
+     *
+     * Themes that only tell the sides apart by colour fall back to the classic discs.
+     */
+    pub fn without_colour(self) -> Theme {
+        let discs = if self.player_disc == self.cpu_disc { Theme::CLASSIC } else { self };
+
+        Theme {
+            player: None,
+            cpu: None,
+            label: None,
+            player_name: discs.player_name,
+            cpu_name: discs.cpu_name,
+            player_disc: discs.player_disc,
+            cpu_disc: discs.cpu_disc,
+            colour: false,
+            ..self
+        }
+    }
+
+    /**
+     * The theme as it should be used on stdout: without colours if stdout is not a terminal
+     * or the NO_COLOR environment variable is set
+     */
+    pub fn for_stdout(self) -> Theme {
+        if colour_supported() { self } else { self.without_colour() }
+    }
+
+    /**
+     * Paints text in the Player's colour
+     */
+    pub fn paint_player(&self, text: &str) -> String {
+        self.paint(self.player.map(Style::from), text)
+    }
+
+    /**
+     * Paints text in the CPU's colour
+     */
+    pub fn paint_cpu(&self, text: &str) -> String {
+        self.paint(self.cpu.map(Style::from), text)
+    }
+
+    /**
+     * Paints text in bold, e.g. headings and commands
+     */
+    pub fn bold(&self, text: &str) -> String {
+        self.paint(Some(Style::new().bold()), text)
+    }
+
+    /**
+     * Paints square labels, e.g. analysis ranks
+     */
+    pub fn highlight(&self, text: &str) -> String {
+        self.paint(Some(self.label.map_or(Style::new().bold(), |c| c.bold())), text)
+    }
+
+    /**
+     * The Player's disc, painted
+     */
+    pub fn player_disc(&self) -> String {
+        self.paint_player(&self.player_disc.to_string())
+    }
+
+    /**
+     * The CPU's disc, painted
+     */
+    pub fn cpu_disc(&self) -> String {
+        self.paint_cpu(&self.cpu_disc.to_string())
+    }
+
+    fn paint(&self, style: Option<Style>, text: &str) -> String {
+        match style {
+            Some(s) if self.colour => s.paint(text).to_string(),
+            _ => text.to_string()
+        }
+    }
+}
+
+impl Default for Theme {
+
+    /**
+     * The red-green theme, without colours if stdout does not support them
+     */
+    fn default() -> Theme {
+        Theme::RED_GREEN.for_stdout()
+    }
+}
+
+/**
+ * Returns whether stdout is a terminal and NO_COLOR is not set (see https://no-color.org)
+ */
+pub fn colour_supported() -> bool {
+    let no_colour = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    !no_colour && stdout().is_terminal()
+}
+
+/**
+ * Sets the theme used by Board::print() and the front-ends
+ */
+pub fn set_theme(theme: Theme) {
+    if let Ok(mut current) = CURRENT.write() {
+        *current = Some(theme);
+    }
+}
+
+/**
+ * Returns the theme set with set_theme(), or the default theme
+ */
+pub fn current() -> Theme {
+    CURRENT.read().ok().and_then(|t| *t).unwrap_or_default()
+}
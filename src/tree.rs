@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use std::io::stderr;
+use std::io::Write;
+use std::time::Instant;
+
+use rand::Rng;
+use rand_distr::{Distribution, Gamma};
+
+use crate::clock::{top_two, MoveBudget};
+use crate::nn::Network;
+use crate::playout::PlayoutPolicies;
+use crate::search::Outcomes;
+use crate::{random_playout, Board, MoveStats, SearchProgress, SearchResult, Searcher, WEIGHTS};
+
+/**
+ * Settings for the AlphaZero style PUCT search
+ *
+ *      c_puct            -    exploration constant, higher values trust the priors for longer
+ *      dirichlet_alpha   -    concentration of the noise added to the root priors
+ *      dirichlet_epsilon -    share of the root priors replaced by noise, 0 disables the noise
+ *      temperature       -    0 plays the most visited move, higher values sample moves
+ *                             in proportion to visits^(1 / temperature)
+ *      rave              -    blends All-Moves-As-First statistics into Q when set
+ */
+#[derive(Clone)]
+pub struct PuctConfig {
+    pub c_puct: f32,
+    pub dirichlet_alpha: f32,
+    pub dirichlet_epsilon: f32,
+    pub temperature: f32,
+    pub rave: Option<Rave>
+}
+
+/**
+ * β schedule for RAVE, the weight given to the AMAF value of a child
+ *
+ *      Equivalence(k)  -   β = sqrt(k / (3N + k)), AMAF and real values are trusted
+ *                          equally after roughly k visits
+ *      MinMse(b)       -   β = Ñ / (N + Ñ + 4b^2 NÑ), the minimum MSE schedule where b
+ *                          is the estimated bias of the AMAF values
+ */
+#[derive(Clone, Copy)]
+pub enum Rave {
+    Equivalence(f32),
+    MinMse(f32)
+}
+
+impl Rave {
+
+    fn beta(&self, visits: u32, amaf_visits: u32) -> f32 {
+        let n = visits as f32;
+        let n_amaf = amaf_visits as f32;
+
+        match self {
+            Rave::Equivalence(k) => (k / (3.0 * n + k)).sqrt(),
+            Rave::MinMse(bias) => {
+                if amaf_visits == 0 {
+                    0.0
+                } else {
+                    n_amaf / (n + n_amaf + 4.0 * bias * bias * n * n_amaf)
+                }
+            }
+        }
+    }
+}
+
+impl Default for PuctConfig {
+    fn default() -> PuctConfig {
+        PuctConfig {
+            c_puct: 1.5,
+            dirichlet_alpha: 0.3,
+            dirichlet_epsilon: 0.0,
+            temperature: 0.0,
+            rave: None
+        }
+    }
+}
+
+/**
+ * Search tree node
+ *
+ * value_sum is stored from the perspective of `side`, the player who played `action`
+ * to reach this node, so a parent can compare its children directly. The amaf_* fields
+ * count every simulation through the parent in which `side` played `action` at any point.
+ */
+struct Node {
+    action: u8,
+    side: u8,
+    prior: f32,
+    visits: u32,
+    value_sum: f32,
+    amaf_visits: u32,
+    amaf_value_sum: f32,
+    expanded: bool,
+    children: Vec<Node>
+}
+
+impl Node {
+
+    fn new(action: u8, side: u8, prior: f32) -> Node {
+        Node {
+            action,
+            side,
+            prior,
+            visits: 0,
+            value_sum: 0.0,
+            amaf_visits: 0,
+            amaf_value_sum: 0.0,
+            expanded: false,
+            children: Vec::new()
+        }
+    }
+
+    /**
+     * Mean value of the node, unvisited nodes are treated as a draw
+     */
+    fn q(&self) -> f32 {
+        if self.visits == 0 {
+            0.5
+        } else {
+            self.value_sum / self.visits as f32
+        }
+    }
+
+    /**
+     * Mean AMAF value of the node, unvisited nodes are treated as a draw
+     */
+    fn amaf_q(&self) -> f32 {
+        if self.amaf_visits == 0 {
+            0.5
+        } else {
+            self.amaf_value_sum / self.amaf_visits as f32
+        }
+    }
+}
+
+/**
+ * Monte Carlo Tree Search using PUCT selection
+ *
+ * Each child keeps a prior from the network policy if one is given, otherwise from a
+ * softmax over the positional weight table. Children are selected by
+ *      Q + c_puct * P * sqrt(N_parent) / (1 + N)
+ * and leaves are evaluated by the network value or a playout using the playout policies.
+ * With RAVE enabled, Q is blended with the AMAF value as (1 - β)Q + βQ_amaf.
+ *
+ *  Parameters:
+ *      b              -    the current board state, searched for the side to move
+ *      max_steps      -    maximum number of simulations
+ *      budget         -    time budget of the search, see MoveBudget::should_stop()
+ *      searcher       -    playout policies, optional network and progress callback
+ *      config         -    PUCT constants, root noise and move selection temperature
+ *      debug          -    used to print extra debug statements
+ *
+ * @returns:    None if the side to move has no actions
+ */
+pub fn puct_search(b: &Board, max_steps: usize, budget: &MoveBudget, searcher: &Searcher, config: &PuctConfig, debug: bool) -> Option<SearchResult> {
+
+    let (policies, network) = (&searcher.policies, searcher.network);
+    let start_time = Instant::now();
+    let mut root = Node::new(0, 0, 1.0);
+    let me: u8 = if b.is_player_turn() { 1 } else { 2 };
+    let mut outcomes: HashMap<u8, Outcomes> = HashMap::new();
+
+    expand(&mut root, b, network);
+
+    // Nothing to search if there are no options or only one
+    match root.children.len() {
+        0 => return None,
+        1 => return Some(search_result(&root, root.children[0].action, &HashMap::new(), start_time)),
+        _ => ()
+    }
+
+    if config.dirichlet_epsilon > 0.0 {
+        add_dirichlet_noise(&mut root, config);
+    }
+
+    if debug { println!("CPU performing up to {} PUCT simulations...", max_steps); }
+
+    for i in 0..max_steps {
+
+        if !debug && i % 10 == 0 && searcher.progress.is_none() {
+            eprint!(".");
+            stderr().flush().unwrap();
+            if (i / 10 + 1) % 30 == 0 { eprintln!() }
+        }
+
+        if let (Some(progress), true) = (searcher.progress, i % 10 == 0) {
+            if let Some(child) = root.children.iter().max_by_key(|c| c.visits) {
+                progress(&SearchProgress { playouts: i, best: child.action, win_rate: child.q() });
+            }
+        }
+
+        // Break out of function when the budget is used or the best play can't be overtaken
+        let (best, second) = top_two(root.children.iter().map(|c| c.visits as usize));
+        if budget.should_stop(start_time.elapsed(), i, best, second) || searcher.stopped() {
+            if debug { println!("Simulations per second: {:.0}", i as f64 / start_time.elapsed().as_secs_f64()); }
+            break;
+        }
+
+        let mut sim_board = b.clone();
+        let mut moves: Vec<(u8, u8)> = Vec::new();
+        simulate(&mut root, &mut sim_board, policies, network, config, &mut moves);
+
+        // Simulations that played to the end of the game count towards the root move's outcomes
+        if let (Some((_, first)), true) = (moves.first(), sim_board.check_game_state(false) != 0) {
+            outcomes.entry(*first).or_default().record(&sim_board, me);
+        }
+    }
+
+    if debug {
+        for child in root.children.iter() {
+            println!("{}: visits {}, Q {:.3}, AMAF {} / {:.3}, prior {:.3}",
+                child.action, child.visits, child.q(), child.amaf_visits, child.amaf_q(), child.prior);
+        }
+    }
+
+    Some(search_result(&root, select_move(&root, config.temperature), &outcomes, start_time))
+}
+
+/**
+ * Collects the root children's statistics, most visited first, and the principal variation
+ */
+fn search_result(root: &Node, best: u8, outcomes: &HashMap<u8, Outcomes>, start_time: Instant) -> SearchResult {
+    let mut moves: Vec<MoveStats> = root.children.iter().map(|c| {
+        outcomes.get(&c.action).cloned().unwrap_or_default().stats(c.action, c.visits, c.q())
+    }).collect();
+    moves.sort_by_key(|m| std::cmp::Reverse(m.visits));
+
+    SearchResult {
+        best,
+        moves,
+        pv: principal_variation(root, best),
+        playouts: root.visits as usize,
+        elapsed: start_time.elapsed()
+    }
+}
+
+/**
+ * Follows best, then the most visited child, down the tree
+ */
+fn principal_variation(root: &Node, best: u8) -> Vec<u8> {
+    let mut pv = Vec::new();
+    let mut node = root.children.iter().find(|c| c.action == best);
+
+    while let Some(n) = node {
+        pv.push(n.action);
+        node = n.children.iter().filter(|c| c.visits > 0).max_by_key(|c| c.visits);
+    }
+
+    pv
+}
+
+/**
+ * Runs one simulation from node down to a leaf and backs up the result
+ *
+ * Every (player, position) played below node, in the tree and in the playout, is
+ * appended to moves so the AMAF statistics of node's children can be updated.
+ *
+ * @returns:    value of the simulation from the Player's (1) perspective
+ */
+fn simulate(node: &mut Node, b: &mut Board, policies: &PlayoutPolicies, network: Option<&Network>, config: &PuctConfig, moves: &mut Vec<(u8, u8)>) -> f32 {
+
+    let first_move = moves.len();
+
+    let value = match b.check_game_state(false) {
+        0 => {
+            if !node.expanded {
+                match expand(node, b, network) {
+                    // Network value is for the side to move
+                    Some(v) => if b.is_player_turn() { v } else { 1.0 - v },
+                    None => outcome_value(random_playout(b, policies, Some(moves), false))
+                }
+            } else {
+                let index = select_child(node, config);
+                let child = &mut node.children[index];
+                b.ins(child.action, child.side, false);
+                moves.push((child.side, child.action));
+                simulate(child, b, policies, network, config, moves)
+            }
+        },
+        state => outcome_value(state)
+    };
+
+    node.visits += 1;
+    node.value_sum += if node.side == 2 { 1.0 - value } else { value };
+
+    if config.rave.is_some() && !node.children.is_empty() {
+        update_amaf(node, &moves[first_move..], value);
+    }
+
+    value
+}
+
+/**
+ * Credits the simulation to every child whose move was played by the same player
+ * later in the simulation, as if it had been played first
+ */
+fn update_amaf(node: &mut Node, moves: &[(u8, u8)], value: f32) {
+    let mut played_by: [u8; 64] = [0; 64];
+    for (side, pos) in moves {
+        let pos_usize: usize = (*pos).into();
+        played_by[pos_usize] = *side;
+    }
+
+    for child in node.children.iter_mut() {
+        let pos_usize: usize = child.action.into();
+        if played_by[pos_usize] == child.side {
+            child.amaf_visits += 1;
+            child.amaf_value_sum += if child.side == 2 { 1.0 - value } else { value };
+        }
+    }
+}
+
+/**
+ * Adds a child for every available action with its prior
+ *
+ * @returns:    the network's value estimate for the side to move if a network was used
+ */
+fn expand(node: &mut Node, b: &Board, network: Option<&Network>) -> Option<f32> {
+
+    let side: u8 = if b.is_player_turn() { 1 } else { 2 };
+    node.expanded = true;
+
+    match network {
+        Some(net) => {
+            let (value, priors) = net.evaluate(b);
+            node.children = priors.iter().map(|(a, p)| Node::new(*a, side, *p)).collect();
+            Some(value)
+        },
+        None => {
+            node.children = weight_priors(b).iter().map(|(a, p)| Node::new(*a, side, *p)).collect();
+            None
+        }
+    }
+}
+
+/**
+ * Priors from a softmax over the positional weight table
+ */
+fn weight_priors(b: &Board) -> Vec<(u8, f32)> {
+    let actions = b.get_available_actions(false);
+    let exps: Vec<(u8, f32)> = actions.iter().map(|&a| {
+        let w: usize = a.into();
+        (a, (WEIGHTS[w] as f32 / 20.0).exp())
+    }).collect();
+    let total: f32 = exps.iter().map(|(_, e)| e).sum();
+
+    exps.into_iter().map(|(a, e)| (a, e / total)).collect()
+}
+
+/**
+ * Mixes Dirichlet noise into the root priors so self-play explores different openings
+ */
+fn add_dirichlet_noise(root: &mut Node, config: &PuctConfig) {
+    let gamma = match Gamma::new(config.dirichlet_alpha, 1.0) {
+        Ok(g) => g,
+        Err(_) => return
+    };
+
+    let mut rng = rand::thread_rng();
+    let noise: Vec<f32> = root.children.iter().map(|_| gamma.sample(&mut rng)).collect();
+    let total: f32 = noise.iter().sum();
+    if total <= 0.0 {
+        return;
+    }
+
+    for (child, n) in root.children.iter_mut().zip(noise.iter()) {
+        child.prior = (1.0 - config.dirichlet_epsilon) * child.prior + config.dirichlet_epsilon * n / total;
+    }
+}
+
+/**
+ * Index of the child with the highest PUCT score
+ */
+fn select_child(node: &Node, config: &PuctConfig) -> usize {
+    let sqrt_parent = (node.visits as f32).sqrt();
+    let mut best_index = 0;
+    let mut best_score = f32::NEG_INFINITY;
+
+    for (i, child) in node.children.iter().enumerate() {
+        let q = match &config.rave {
+            Some(rave) => {
+                let beta = rave.beta(child.visits, child.amaf_visits);
+                (1.0 - beta) * child.q() + beta * child.amaf_q()
+            },
+            None => child.q()
+        };
+        let score = q + config.c_puct * child.prior * sqrt_parent / (1.0 + child.visits as f32);
+        if score > best_score {
+            best_score = score;
+            best_index = i;
+        }
+    }
+
+    best_index
+}
+
+/**
+ * Temperatures below this play the most visited move, as 1 / temperature is too large to sample with
+ */
+const MIN_TEMPERATURE: f32 = 1e-3;
+
+/**
+ * Picks the move to play from the root visit counts using the temperature
+ */
+fn select_move(root: &Node, temperature: f32) -> u8 {
+    let most_visited = root.children.iter().max_by_key(|c| c.visits).unwrap();
+    if temperature < MIN_TEMPERATURE || most_visited.visits == 0 {
+        return most_visited.action;
+    }
+
+    // Visits are scaled by the highest count first so visits^(1 / temperature) can't overflow
+    let max_visits = most_visited.visits as f32;
+    let weights: Vec<f32> = root.children.iter().map(|c| (c.visits as f32 / max_visits).powf(1.0 / temperature)).collect();
+    let total: f32 = weights.iter().sum();
+
+    let mut r: f32 = rand::thread_rng().gen::<f32>() * total;
+    for (child, w) in root.children.iter().zip(weights.iter()) {
+        if r < *w {
+            return child.action;
+        }
+        r -= w;
+    }
+
+    most_visited.action
+}
+
+/**
+ * Converts a check_game_state() result into a value for the Player (1)
+ */
+fn outcome_value(state: u8) -> f32 {
+    match state {
+        1 => 1.0,
+        2 => 0.0,
+        _ => 0.5
+    }
+}
@@ -0,0 +1,353 @@
+use std::cell::Cell;
+use std::io;
+use std::io::{stdout, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{read, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton, MouseEventKind};
+use crossterm::style::{Attribute, Color, Print, SetAttribute, SetBackgroundColor, SetForegroundColor, ResetColor};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use ansi_term::Colour;
+
+use reversi::clock::{MoveBudget, TimeManager};
+use reversi::theme;
+use reversi::theme::Theme;
+use reversi::{convert_num, Board, Engine, GameRecord, SearchProgress};
+
+use crate::stream::Broadcaster;
+
+/**
+ * Screen layout: the board's top left corner, the move list panel and the status lines
+ */
+const BOARD_ROW: u16 = 2;
+const BOARD_COL: u16 = 4;
+const PANEL_COL: u16 = 26;
+const PANEL_ROWS: usize = 9;
+const SCORE_ROW: u16 = 12;
+const CLOCK_ROW: u16 = 13;
+const STATUS_ROW: u16 = 15;
+const HELP_ROW: u16 = 17;
+
+/**
+ * Least time between two redraws of the thinking indicator
+ */
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/**
+ * Puts the terminal in full-screen raw mode and restores it when dropped, also when the
+ * game ends with an error
+ */
+struct Screen;
+
+impl Screen {
+
+    fn open() -> io::Result<Screen> {
+        enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen, EnableMouseCapture, Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), Show, DisableMouseCapture, LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+/**
+ * State of a full-screen game
+ *
+ *      cursor      -   (row, col) of the square the player is pointing at
+ *      player_time -   time the player has spent on their moves
+ *      status      -   message shown under the clocks
+ */
+struct Game {
+    board: Board,
+    record: GameRecord,
+    cursor: (u8, u8),
+    cpu_clock: Option<TimeManager>,
+    player_time: Duration,
+    status: String
+}
+
+/**
+ * Plays the Player against the CPU full-screen: the board is redrawn in place, squares are
+ * picked with the arrow keys and Enter or with mouse clicks, and the CPU's search progress
+ * is shown while it thinks
+ *
+ *  Parameters:
+ *      engine      -   the CPU, its progress callback should be progress_reporter()
+ *      record      -   the game so far, usually empty or only holding a set up position
+ *      cpu_clock   -   optional game clock for the CPU, otherwise it searches secs per move
+ *      broadcaster -   optional spectator stream sent every move
+ */
+pub fn run(engine: &mut dyn Engine, record: GameRecord, cpu_clock: Option<TimeManager>, broadcaster: Option<&Broadcaster>, secs: u64) {
+    let screen = match Screen::open() {
+        Ok(s) => s,
+        Err(e) => {
+            println!("ERROR: could not start the full-screen UI: {}", e);
+            return;
+        }
+    };
+
+    let mut game = Game {
+        board: record.board(),
+        record,
+        cursor: (2, 3),
+        cpu_clock,
+        player_time: Duration::new(0, 0),
+        status: String::from("Your move")
+    };
+
+    if let Err(e) = play(&mut game, engine, broadcaster, secs) {
+        drop(screen);
+        println!("ERROR: {}", e);
+    }
+}
+
+fn play(game: &mut Game, engine: &mut dyn Engine, broadcaster: Option<&Broadcaster>, secs: u64) -> io::Result<()> {
+    loop {
+        if game.board.check_game_state(false) != 0 {
+            let (player_score, cpu_score) = game.board.get_score();
+            game.status = format!("{} {} to {}, press any key to exit",
+                match game.board.check_game_state(false) {
+                    1 => "You won",
+                    2 => "The CPU won",
+                    _ => "Draw"
+                }, player_score, cpu_score);
+            draw(game)?;
+            wait_for_key()?;
+            return Ok(());
+        }
+
+        draw(game)?;
+
+        if game.board.is_player_turn() {
+            let turn_start = Instant::now();
+            let pos = match pick_square(game)? {
+                Some(p) => p,
+                None => return Ok(())
+            };
+            game.player_time += turn_start.elapsed();
+            game.record.play(&mut game.board, pos).map_err(io::Error::other)?;
+            if let Some(b) = broadcaster {
+                b.board(&game.board, 1, pos);
+            }
+            game.status = String::from("CPU thinking...");
+        } else {
+            let budget = match &game.cpu_clock {
+                Some(clock) => {
+                    let (player_score, cpu_score) = game.board.get_score();
+                    clock.allocate(64 - player_score - cpu_score)
+                },
+                None => MoveBudget::fixed(secs)
+            };
+
+            let move_start = Instant::now();
+            engine.set_position(&game.board);
+            if let Some(b) = broadcaster {
+                b.follow_search(true);
+            }
+            let best = engine.choose_move(&budget);
+            if let Some(b) = broadcaster {
+                b.follow_search(false);
+            }
+            let best = match best {
+                Some(pos) => pos,
+                None => return Ok(())
+            };
+            if let Some(clock) = game.cpu_clock.as_mut() {
+                clock.record(move_start.elapsed());
+            }
+
+            game.record.play(&mut game.board, best).map_err(io::Error::other)?;
+            if let Some(b) = broadcaster {
+                b.board(&game.board, 2, best);
+            }
+            let win_rate = engine.statistics().and_then(|r| r.best_stats()).map_or(0.5, |m| m.value);
+            game.status = format!("CPU played {} ({:.0}% to win), your move", convert_num(best), win_rate * 100.0);
+        }
+    }
+}
+
+/**
+ * Handles input until the player picks an available action, or returns None if they quit
+ */
+fn pick_square(game: &mut Game) -> io::Result<Option<u8>> {
+    loop {
+        let actions = game.board.get_available_actions(false);
+        let (row, col) = game.cursor;
+
+        let picked: Option<u8> = match read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Up => { game.cursor.0 = row.saturating_sub(1); None },
+                KeyCode::Down => { game.cursor.0 = (row + 1).min(7); None },
+                KeyCode::Left => { game.cursor.1 = col.saturating_sub(1); None },
+                KeyCode::Right => { game.cursor.1 = (col + 1).min(7); None },
+                KeyCode::Enter | KeyCode::Char(' ') => Some(row * 8 + col),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+                _ => None
+            },
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                match square_at(mouse.column, mouse.row) {
+                    Some((r, c)) => {
+                        game.cursor = (r, c);
+                        Some(r * 8 + c)
+                    },
+                    None => None
+                }
+            },
+            _ => None
+        };
+
+        match picked {
+            Some(pos) if actions.contains(&pos) => return Ok(Some(pos)),
+            Some(pos) => game.status = format!("{} is not an available move", convert_num(pos)),
+            None => ()
+        }
+
+        draw(game)?;
+    }
+}
+
+/**
+ * Maps a terminal cell to the (row, col) of the board square drawn there
+ */
+fn square_at(x: u16, y: u16) -> Option<(u8, u8)> {
+    if x < BOARD_COL || y < BOARD_ROW + 1 {
+        return None;
+    }
+
+    let col = (x - BOARD_COL) / 2;
+    let row = y - BOARD_ROW - 1;
+    if col < 8 && row < 8 {
+        Some((row as u8, col as u8))
+    } else {
+        None
+    }
+}
+
+fn wait_for_key() -> io::Result<()> {
+    loop {
+        match read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => return Ok(()),
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => return Ok(()),
+            _ => ()
+        }
+    }
+}
+
+/**
+ * Redraws the whole screen
+ */
+fn draw(game: &Game) -> io::Result<()> {
+    let mut out = stdout();
+    let theme: Theme = theme::current();
+    let actions = game.board.get_available_actions(false);
+    let show_actions = game.board.is_player_turn();
+
+    queue!(out, Clear(ClearType::All), MoveTo(BOARD_COL, 0), SetAttribute(Attribute::Bold), Print("Reversi"), SetAttribute(Attribute::Reset))?;
+    queue!(out, MoveTo(BOARD_COL, BOARD_ROW), Print("A B C D E F G H"))?;
+
+    for row in 0..8u8 {
+        queue!(out, MoveTo(BOARD_COL - 2, BOARD_ROW + 1 + row as u16), Print(row + 1))?;
+
+        for col in 0..8u8 {
+            let pos = row * 8 + col;
+            queue!(out, MoveTo(BOARD_COL + col as u16 * 2, BOARD_ROW + 1 + row as u16))?;
+
+            if show_actions && game.cursor == (row, col) && theme.colour {
+                queue!(out, SetBackgroundColor(Color::DarkGrey))?;
+            } else if show_actions && game.cursor == (row, col) {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+
+            match game.board.squares()[pos as usize] {
+                1 => queue!(out, SetForegroundColor(colour(theme.player)), Print(theme.player_disc))?,
+                2 => queue!(out, SetForegroundColor(colour(theme.cpu)), Print(theme.cpu_disc))?,
+                _ if show_actions && actions.contains(&pos) => queue!(out, SetForegroundColor(colour(theme.label)), Print(theme.action))?,
+                _ => queue!(out, Print(theme.empty))?
+            }
+            queue!(out, ResetColor, SetAttribute(Attribute::Reset))?;
+        }
+    }
+
+    // Move list, one numbered line per pair of moves, the latest moves at the bottom
+    let moves: Vec<String> = game.record.moves().iter().map(|mv| mv.map_or(String::from("PA"), convert_num)).collect();
+    let lines: Vec<String> = moves.chunks(2).enumerate()
+        .map(|(i, pair)| format!("{:>2}. {}", i + 1, pair.join(" ")))
+        .collect();
+    queue!(out, MoveTo(PANEL_COL, BOARD_ROW), SetAttribute(Attribute::Bold), Print("Moves"), SetAttribute(Attribute::Reset))?;
+    for (i, line) in lines.iter().skip(lines.len().saturating_sub(PANEL_ROWS - 1)).enumerate() {
+        queue!(out, MoveTo(PANEL_COL, BOARD_ROW + 1 + i as u16), Print(line))?;
+    }
+
+    let (player_score, cpu_score) = game.board.get_score();
+    queue!(out, MoveTo(BOARD_COL, SCORE_ROW), Print("Player: "), SetForegroundColor(colour(theme.player)), Print(player_score),
+        ResetColor, Print("  CPU: "), SetForegroundColor(colour(theme.cpu)), Print(cpu_score), ResetColor)?;
+
+    let cpu_clock = match &game.cpu_clock {
+        Some(clock) => format_duration(clock.remaining()),
+        None => String::from("--:--")
+    };
+    queue!(out, MoveTo(BOARD_COL, CLOCK_ROW), Print(format!("Your time: {}  CPU clock: {}", format_duration(game.player_time), cpu_clock)))?;
+
+    queue!(out, MoveTo(BOARD_COL, STATUS_ROW), Print(&game.status))?;
+    queue!(out, MoveTo(BOARD_COL, HELP_ROW), SetAttribute(Attribute::Dim),
+        Print("Arrows / mouse: pick a square   Enter / click: play   q: quit"), SetAttribute(Attribute::Reset))?;
+
+    out.flush()
+}
+
+/**
+ * Converts a theme colour to the terminal's, None being the terminal's own colour
+ */
+fn colour(c: Option<Colour>) -> Color {
+    match c {
+        Some(Colour::Black) => Color::DarkGrey,
+        Some(Colour::Red) => Color::DarkRed,
+        Some(Colour::Green) => Color::DarkGreen,
+        Some(Colour::Yellow) => Color::DarkYellow,
+        Some(Colour::Blue) => Color::DarkBlue,
+        Some(Colour::Purple) => Color::DarkMagenta,
+        Some(Colour::Cyan) => Color::DarkCyan,
+        Some(Colour::White) => Color::Grey,
+        Some(Colour::Fixed(n)) => Color::AnsiValue(n),
+        Some(Colour::RGB(r, g, b)) => Color::Rgb { r, g, b },
+        None => Color::Reset
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/**
+ * Returns a search progress callback that redraws the thinking indicator on the status
+ * line, at most once every PROGRESS_INTERVAL
+ */
+pub fn progress_reporter() -> impl Fn(&SearchProgress) {
+    let last_draw: Cell<Option<Instant>> = Cell::new(None);
+    let frame: Cell<usize> = Cell::new(0);
+
+    move |p: &SearchProgress| {
+        if last_draw.get().is_some_and(|t| t.elapsed() < PROGRESS_INTERVAL) {
+            return;
+        }
+        last_draw.set(Some(Instant::now()));
+        frame.set(frame.get() + 1);
+
+        let mut out = stdout();
+        let _ = queue!(out, MoveTo(BOARD_COL, STATUS_ROW), Clear(ClearType::CurrentLine),
+            Print(format!("CPU thinking {} {} playouts, best {} ({:.0}% to win)",
+                SPINNER[frame.get() % SPINNER.len()], p.playouts, convert_num(p.best), p.win_rate * 100.0)));
+        let _ = out.flush();
+    }
+}
@@ -0,0 +1,274 @@
+// Regression tests for the rules engine on known positions
+mod common;
+
+use common::{flips, legal_moves, position, ray, sorted};
+use reversi::perft::perft;
+use reversi::{convert_2d, convert_num, flipped, get_new_pos, parse_pos, Board};
+
+fn sq(s: &str) -> u8 {
+    parse_pos(s).unwrap()
+}
+
+#[test]
+fn start_position() {
+    let b = Board::new(8, 8);
+
+    assert!(b.is_player_turn());
+    assert_eq!(b.get_score(), (2, 2));
+    assert_eq!(sorted(&b.get_player_actions()), vec![sq("d3"), sq("c4"), sq("f5"), sq("e6")]);
+    assert_eq!(sorted(&b.get_cpu_actions()), vec![sq("e3"), sq("f4"), sq("c5"), sq("d6")]);
+    assert_eq!(b.check_game_state(false), 0);
+    assert_eq!(b.check_consistency(), Ok(()));
+}
+
+#[test]
+fn flips_in_all_eight_directions() {
+    let mut b = position([
+        "--------",
+        "-X-X-X--",
+        "--OOO---",
+        "-XO-OX--",
+        "--OOO---",
+        "-X-X-X--",
+        "--------",
+        "--------",
+    ], 'X');
+    let before = b.clone();
+
+    assert!(b.get_available_actions(false).contains(&sq("d4")));
+    b.ins(sq("d4"), 1, false);
+
+    let mut expected: Vec<u8> = ["c3", "d3", "e3", "c4", "e4", "c5", "d5", "e5"].iter().map(|s| sq(s)).collect();
+    expected.sort_unstable();
+    assert_eq!(flipped(&before, &b), expected);
+    assert_eq!(b.get_score(), (17, 0));
+    assert!(!b.is_player_turn());
+    assert_eq!(b.check_consistency(), Ok(()));
+}
+
+#[test]
+fn flips_each_direction_alone() {
+    // One sandwich at a time around d4, in get_new_pos() direction order
+    for dir in 0..8 {
+        let line = ray(sq("d4"), dir);
+        let mut squares = [0_u8; 64];
+        squares[line[0] as usize] = 2;
+        squares[line[1] as usize] = 1;
+
+        let mut b = Board::from_squares(&squares, true).unwrap();
+        let before = b.clone();
+        b.ins(sq("d4"), 1, false);
+
+        assert_eq!(flipped(&before, &b), vec![line[0]], "direction {}", dir);
+        assert_eq!(b.get_score(), (3, 0), "direction {}", dir);
+    }
+}
+
+#[test]
+fn flips_stop_at_the_first_sandwiching_disc() {
+    let mut b = position([
+        "-OXOX---",
+        "--------",
+        "--------",
+        "--------",
+        "--------",
+        "--------",
+        "--------",
+        "--------",
+    ], 'X');
+    let before = b.clone();
+
+    b.ins(sq("a1"), 1, false);
+    assert_eq!(flipped(&before, &b), vec![sq("b1")]);
+    assert_eq!(b.squares()[sq("d1") as usize], 2);
+}
+
+#[test]
+fn no_flips_across_a_gap() {
+    let b = position([
+        "--------",
+        "--------",
+        "--------",
+        "-XO-O---",
+        "--------",
+        "--------",
+        "--------",
+        "--------",
+    ], 'X');
+
+    // f4 would need the line through the empty d4
+    assert!(!b.get_player_actions().contains(&sq("f4")));
+    assert_eq!(sorted(&b.get_player_actions()), vec![sq("d4")]);
+}
+
+#[test]
+fn no_moves_wrapping_around_the_edge() {
+    // h1 and a2 are next to each other as indices, but not on the board
+    let b = position([
+        "-------O",
+        "X-------",
+        "--------",
+        "--------",
+        "--------",
+        "--------",
+        "--------",
+        "--------",
+    ], 'X');
+    assert!(!b.get_player_actions().contains(&sq("g1")));
+
+    let b = position([
+        "--------",
+        "-------X",
+        "O-------",
+        "--------",
+        "--------",
+        "--------",
+        "--------",
+        "--------",
+    ], 'X');
+    assert!(!b.get_player_actions().contains(&sq("b3")));
+    assert!(b.get_player_actions().is_empty());
+    assert_eq!(b.check_consistency(), Ok(()));
+}
+
+#[test]
+fn illegal_moves_change_nothing() {
+    let mut b = Board::new(8, 8);
+    let before = b.clone();
+
+    b.ins(sq("a1"), 1, false);
+    b.ins(sq("d4"), 1, false);
+    assert_eq!(b.squares(), before.squares());
+    assert!(b.is_player_turn());
+}
+
+#[test]
+fn get_new_pos_edges() {
+    let cases = [
+        (0, "h1", None), (0, "g1", Some("h1")),
+        (1, "a2", None), (1, "b2", Some("a2")),
+        (2, "h8", None), (2, "a7", Some("a8")),
+        (3, "a1", None), (3, "h2", Some("h1")),
+        (4, "a5", None), (4, "e1", None), (4, "b2", Some("a1")),
+        (5, "h5", None), (5, "e1", None), (5, "g2", Some("h1")),
+        (6, "a5", None), (6, "e8", None), (6, "b7", Some("a8")),
+        (7, "h1", None), (7, "h5", None), (7, "e8", None), (7, "g7", Some("h8")),
+    ];
+
+    for (dir, from, expected) in cases.iter() {
+        assert_eq!(get_new_pos(*dir, sq(from), 1, 64), expected.map(sq), "direction {} from {}", dir, from);
+    }
+    assert_eq!(get_new_pos(8, sq("d4"), 1, 64), None);
+}
+
+#[test]
+fn get_new_pos_walks_stay_on_the_board() {
+    // Walking a direction until the first None visits exactly the squares up to the edge
+    for pos in 0..64 {
+        for dir in 0..8 {
+            let walk: Vec<u8> = (1..).map_while(|iter| get_new_pos(dir as u8, pos, iter, 64)).collect();
+            assert_eq!(walk, ray(pos, dir), "direction {} from {}", dir, convert_num(pos));
+        }
+    }
+}
+
+#[test]
+fn notation_round_trips() {
+    for pos in 0..64 {
+        let name = convert_num(pos);
+        assert_eq!(parse_pos(&name), Some(pos));
+        assert_eq!(parse_pos(&name.to_lowercase()), Some(pos));
+        assert_eq!(convert_2d(&name), pos);
+    }
+
+    assert_eq!(convert_num(0), "A1");
+    assert_eq!(convert_num(63), "H8");
+    for bad in ["", "a", "i1", "a0", "a9", "a10", "11", "d3 "].iter() {
+        assert_eq!(parse_pos(bad), None, "{:?}", bad);
+    }
+}
+
+#[test]
+fn obf_round_trips() {
+    let start = "---------------------------OX------XO--------------------------- X";
+    let b = Board::from_obf(start).unwrap();
+    assert_eq!(b.to_obf(), start);
+    assert_eq!(b.squares(), Board::new(8, 8).squares());
+
+    let b = Board::from_obf("---------------------------OX------XO--------------------------- O; e3:+0").unwrap();
+    assert!(!b.is_player_turn());
+
+    assert!(Board::from_obf("---").is_err());
+    assert!(Board::from_obf(&format!("{} Z", "-".repeat(64))).is_err());
+    assert!(Board::from_obf(&format!("{}Q X", "-".repeat(63))).is_err());
+}
+
+#[test]
+fn terminal_positions() {
+    // Wipeout
+    let b = position(["XXX-----", "--------", "--------", "--------", "--------", "--------", "--------", "--------"], 'O');
+    assert_eq!(b.check_game_state(false), 1);
+
+    // Full board, 32 discs each
+    let mut squares = [1_u8; 64];
+    squares[32..].iter_mut().for_each(|val| *val = 2);
+    let b = Board::from_squares(&squares, true).unwrap();
+    assert_eq!(b.get_score(), (32, 32));
+    assert_eq!(b.check_game_state(false), 3);
+
+    // The game also ends when only one side is out of moves
+    let b = position(["OX------", "--------", "--------", "--------", "--------", "--------", "--------", "--------"], 'X');
+    assert!(b.get_player_actions().is_empty());
+    assert_eq!(sorted(&b.get_cpu_actions()), vec![sq("c1")]);
+    assert_eq!(b.check_game_state(false), 3);
+
+    let b = position(["OXX-----", "--------", "--------", "--------", "--------", "--------", "--------", "--------"], 'X');
+    assert_eq!(b.check_game_state(false), 1);
+}
+
+#[test]
+fn matches_the_reference_rules() {
+    let b = position([
+        "--XXXO--",
+        "-OOOXO--",
+        "XOXOXXO-",
+        "-XOXOX--",
+        "OXXOXO-X",
+        "--OXO-O-",
+        "-X-O-X--",
+        "--------",
+    ], 'O');
+
+    assert_eq!(sorted(&b.get_player_actions()), legal_moves(b.squares(), 1));
+    assert_eq!(sorted(&b.get_cpu_actions()), legal_moves(b.squares(), 2));
+    assert_eq!(b.check_consistency(), Ok(()));
+
+    for pos in b.get_cpu_actions() {
+        let mut after = b.clone();
+        after.ins(pos, 2, false);
+        assert_eq!(flipped(&b, &after), flips(b.squares(), pos, 2), "{}", convert_num(pos));
+    }
+}
+
+#[test]
+fn set_square_checks_its_arguments() {
+    let mut b = Board::new(8, 8);
+
+    assert!(b.set_square(64, 1).is_err());
+    assert!(b.set_square(0, 3).is_err());
+    assert_eq!(b.squares(), Board::new(8, 8).squares());
+
+    assert_eq!(b.set_square(sq("a1"), 2), Ok(()));
+    assert_eq!(b.squares()[0], 2);
+    assert_eq!(b.check_consistency(), Ok(()));
+}
+
+#[test]
+fn perft_from_the_start() {
+    let b = Board::new(8, 8);
+    let expected = [1, 4, 12, 56, 244, 1396, 8200];
+
+    for (depth, count) in expected.iter().enumerate() {
+        assert_eq!(perft(&b, depth as u32), *count, "depth {}", depth);
+    }
+}
@@ -0,0 +1,83 @@
+// Time allocation and the search stopping rules of the game clock
+use std::time::Duration;
+
+use reversi::clock::{top_two, MoveBudget, TimeManager};
+
+fn secs(d: Duration) -> f64 {
+    d.as_secs_f64()
+}
+
+fn assert_secs(d: Duration, expected: f64) {
+    assert!((secs(d) - expected).abs() < 1e-6, "{}s, expected {}s", secs(d), expected);
+}
+
+#[test]
+fn allocation_follows_the_empty_squares() {
+    // 57s are usable after the 5% margin
+    let clock = TimeManager::new(Duration::from_secs(60), Duration::from_secs(0));
+
+    // 60 empties leave 30 moves for the side to move, the maximum is 2.5 times the target
+    let budget = clock.allocate(60);
+    assert_secs(budget.target, 1.9);
+    assert_secs(budget.maximum, 4.75);
+
+    // Fewer empties give each move more, and the maximum is capped at half the usable time
+    let budget = clock.allocate(10);
+    assert_secs(budget.target, 11.4);
+    assert_secs(budget.maximum, 28.5);
+
+    // The last move may use everything usable
+    let budget = clock.allocate(0);
+    assert_secs(budget.target, 57.0);
+    assert_secs(budget.maximum, 57.0);
+}
+
+#[test]
+fn increments_are_allocated_and_added() {
+    let mut clock = TimeManager::new(Duration::from_secs(10), Duration::from_secs(2));
+
+    // 9.5s usable over 10 moves, plus this move's increment
+    let budget = clock.allocate(20);
+    assert_secs(budget.target, 2.95);
+    assert_secs(budget.maximum, 4.75);
+
+    clock.record(Duration::from_secs(3));
+    assert_eq!(clock.remaining(), Duration::from_secs(9));
+
+    // Running over the clock leaves only the increment
+    clock.record(Duration::from_secs(20));
+    assert_eq!(clock.remaining(), Duration::from_secs(2));
+}
+
+#[test]
+fn stops_early_when_the_best_move_cannot_be_overtaken() {
+    let budget = MoveBudget { target: Duration::from_secs(1), maximum: Duration::from_secs(2) };
+    let elapsed = Duration::from_millis(900);
+
+    // 900 iterations in 0.9s leave about 100 before the target
+    assert!(budget.should_stop(elapsed, 900, 500, 100));
+    assert!(!budget.should_stop(elapsed, 900, 500, 450));
+
+    // Too few iterations to estimate the rate
+    assert!(!budget.should_stop(elapsed, 10, 10, 0));
+}
+
+#[test]
+fn extends_the_search_when_the_top_two_are_close() {
+    let budget = MoveBudget { target: Duration::from_secs(1), maximum: Duration::from_secs(2) };
+    let elapsed = Duration::from_millis(1200);
+
+    assert!(!budget.should_stop(elapsed, 1200, 100, 95));
+    assert!(budget.should_stop(elapsed, 1200, 100, 50));
+
+    // The maximum is a hard limit
+    assert!(budget.should_stop(Duration::from_secs(2), 2000, 100, 100));
+}
+
+#[test]
+fn top_two_counts() {
+    assert_eq!(top_two(vec![3, 9, 1, 9, 4].into_iter()), (9, 9));
+    assert_eq!(top_two(vec![3, 1, 7].into_iter()), (7, 3));
+    assert_eq!(top_two(vec![5].into_iter()), (5, 0));
+    assert_eq!(top_two(Vec::new().into_iter()), (0, 0));
+}
@@ -0,0 +1,80 @@
+// Helpers shared by the integration tests, not every test file uses all of them
+#![allow(dead_code)]
+
+use reversi::Board;
+
+/**
+ * Row and column steps of the 8 directions, in get_new_pos() order:
+ * right, left, down, up, up left, up right, down left, down right
+ */
+pub const DIRECTIONS: [(i8, i8); 8] = [(0, 1), (0, -1), (1, 0), (-1, 0), (-1, -1), (-1, 1), (1, -1), (1, 1)];
+
+/**
+ * Builds a position from 8 rows of 8 squares (X, O or -), from row 1 to row 8
+ *
+ *  Parameters:
+ *      rows    -   the rows, e.g. "---OX---"
+ *      side    -   'X' for the Player (1) to move, 'O' for the CPU (2)
+ */
+pub fn position(rows: [&str; 8], side: char) -> Board {
+    Board::from_obf(&format!("{} {}", rows.concat(), side)).unwrap()
+}
+
+/**
+ * Squares along a ray from pos in direction dir up to the edge of the board, worked out on
+ * rows and columns rather than indices so it can't wrap around
+ */
+pub fn ray(pos: u8, dir: usize) -> Vec<u8> {
+    let (dr, dc) = DIRECTIONS[dir];
+    let mut row = (pos / 8) as i8;
+    let mut col = (pos % 8) as i8;
+    let mut squares = Vec::new();
+
+    loop {
+        row += dr;
+        col += dc;
+        if !(0..8).contains(&row) || !(0..8).contains(&col) {
+            return squares;
+        }
+        squares.push((row * 8 + col) as u8);
+    }
+}
+
+/**
+ * Reference implementation of the rules: the discs flipped if val (1 or 2) plays pos, sorted
+ *
+ * @returns:    empty if pos is occupied or flips nothing, i.e. is not a legal move
+ */
+pub fn flips(squares: &[u8], pos: u8, val: u8) -> Vec<u8> {
+    let mut flipped = Vec::new();
+    if squares[pos as usize] != 0 {
+        return flipped;
+    }
+
+    for dir in 0..8 {
+        let line = ray(pos, dir);
+        let run = line.iter().take_while(|sq| squares[**sq as usize] == 3 - val).count();
+        if run > 0 && line.get(run).map(|sq| squares[*sq as usize]) == Some(val) {
+            flipped.extend_from_slice(&line[..run]);
+        }
+    }
+
+    flipped.sort_unstable();
+    flipped
+}
+
+/**
+ * Reference implementation of the legal moves of val (1 or 2), sorted
+ */
+pub fn legal_moves(squares: &[u8], val: u8) -> Vec<u8> {
+    (0..64).filter(|pos| !flips(squares, *pos, val).is_empty()).collect()
+}
+
+/**
+ * Returns an action set as a sorted Vec, to compare against legal_moves()
+ */
+pub fn sorted<'a>(actions: impl IntoIterator<Item = &'a u8>) -> Vec<u8> {
+    let mut actions: Vec<u8> = actions.into_iter().cloned().collect();
+    actions.sort_unstable();
+    actions
+}
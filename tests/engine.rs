@@ -0,0 +1,88 @@
+// Engine trait behaviour: stopping searches and playing engines against each other
+use std::time::{Duration, Instant};
+
+use reversi::clock::MoveBudget;
+use reversi::engine::{play_game, play_match, MatchScore};
+use reversi::{Board, Engine, MctsEngine, SearchResult, StopHandle};
+
+/**
+ * Engine that plays the lowest numbered available action without searching
+ */
+struct FirstMove {
+    board: Board,
+    stop: StopHandle
+}
+
+impl FirstMove {
+    fn new() -> FirstMove {
+        FirstMove { board: Board::new(8, 8), stop: StopHandle::new() }
+    }
+}
+
+impl Engine for FirstMove {
+
+    fn name(&self) -> String {
+        String::from("first-move")
+    }
+
+    fn new_game(&mut self) {
+        self.board = Board::new(8, 8);
+    }
+
+    fn set_position(&mut self, b: &Board) {
+        self.board = b.clone();
+    }
+
+    fn play_move(&mut self, mv: Option<u8>) -> Result<(), String> {
+        match mv {
+            Some(pos) => {
+                let val: u8 = if self.board.is_player_turn() { 1 } else { 2 };
+                self.board.ins(pos, val, false);
+            },
+            None => self.board.pass()
+        }
+        Ok(())
+    }
+
+    fn choose_move(&mut self, _budget: &MoveBudget) -> Option<u8> {
+        self.board.get_available_actions(false).iter().min().cloned()
+    }
+
+    fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+
+    fn statistics(&self) -> Option<&SearchResult> {
+        None
+    }
+}
+
+#[test]
+fn stop_before_the_search_is_kept() {
+    let mut engine = MctsEngine::easy();
+    engine.set_position(&Board::new(8, 8));
+
+    engine.stop();
+    let start = Instant::now();
+    assert!(engine.choose_move(&MoveBudget::fixed(60)).is_some());
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // The stop only applied to that search
+    let time = Duration::from_millis(200);
+    engine.choose_move(&MoveBudget { target: time, maximum: time });
+    assert!(engine.statistics().unwrap().playouts > 0);
+}
+
+#[test]
+fn games_between_engines() {
+    let board = play_game(&mut FirstMove::new(), &mut FirstMove::new(), &Board::new(8, 8), &MoveBudget::fixed(1)).unwrap();
+    assert_ne!(board.check_game_state(false), 0);
+
+    // The same deterministic engine on both sides wins as often with each colour
+    let score = play_match(&mut FirstMove::new(), &mut FirstMove::new(), &Board::new(8, 8), 4, &MoveBudget::fixed(1)).unwrap();
+    let expected = match board.check_game_state(false) {
+        3 => MatchScore { wins: 0, draws: 4, losses: 0 },
+        _ => MatchScore { wins: 2, draws: 0, losses: 2 }
+    };
+    assert_eq!(score, expected);
+}
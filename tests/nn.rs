@@ -0,0 +1,83 @@
+// Forward pass and weight file tests for the value/policy network
+use reversi::nn::Network;
+use reversi::{parse_pos, Board};
+
+fn sq(s: &str) -> u8 {
+    parse_pos(s).unwrap()
+}
+
+/**
+ * A network with one hidden unit in the weight file format: the unit is 2 when the Player is
+ * to move plus a bias of 0.5, the value head maps 2.5 to a logit of 0 and the policy head
+ * gives D3 a logit of 1 and every other square 0
+ */
+fn hand_written() -> String {
+    let mut w1 = vec!["0"; 193];
+    w1[192] = "2";
+    let mut wp = vec!["0"; 64];
+    wp[sq("d3") as usize] = "0.4";
+
+    format!("reversi-mlp 1\n{}\n0.5\n0.4\n-1\n{}\n{}\n", w1.join(" "), wp.join(" "), vec!["0"; 64].join(" "))
+}
+
+#[test]
+fn forward_pass_of_a_hand_written_network() {
+    let net = Network::parse(&hand_written()).unwrap();
+    let (value, priors) = net.evaluate(&Board::new(8, 8));
+
+    assert!((value - 0.5).abs() < 1e-6, "value {}", value);
+
+    // Softmax over the legal moves only: e / (e + 3) for D3 and 1 / (e + 3) for the others
+    let e = std::f32::consts::E;
+    assert_eq!(priors.len(), 4);
+    for (action, prior) in priors {
+        let expected = if action == sq("d3") { e / (e + 3.0) } else { 1.0 / (e + 3.0) };
+        assert!((prior - expected).abs() < 1e-6, "{} has prior {}", action, prior);
+    }
+}
+
+#[test]
+fn hidden_units_are_rectified() {
+    // With the CPU to move the unit's input is only its bias, a negative bias gives 0 and
+    // leaves the value at sigmoid(-1)
+    let text = hand_written().replacen("\n0.5\n", "\n-0.5\n", 1);
+    let net = Network::parse(&text).unwrap();
+
+    let mut b = Board::new(8, 8);
+    b.ins(sq("d3"), 1, false);
+    assert!(!b.is_player_turn());
+
+    let (value, _priors) = net.evaluate(&b);
+    assert!((value - 1.0 / (1.0 + 1.0_f32.exp())).abs() < 1e-6, "value {}", value);
+}
+
+#[test]
+fn malformed_weights_are_rejected() {
+    let text = hand_written();
+
+    assert!(Network::parse(&text.replacen("reversi-mlp", "mlp", 1)).is_err());
+    assert!(Network::parse(&text.replacen("reversi-mlp 1", "reversi-mlp 0", 1)).is_err());
+    assert!(Network::parse(&text.replacen("0.4", "x", 1)).is_err());
+    assert!(Network::parse(&format!("{} 0", text)).is_err());
+}
+
+#[test]
+fn positional_network_round_trips() {
+    let net = Network::positional();
+    let path = std::env::temp_dir().join(format!("reversi-nn-{}.txt", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    net.save(path).unwrap();
+    let loaded = Network::load(path);
+    std::fs::remove_file(path).unwrap();
+    let loaded = loaded.unwrap();
+
+    let mut b = Board::new(8, 8);
+    b.ins(sq("d3"), 1, false);
+    assert_eq!(net.evaluate(&b), loaded.evaluate(&b));
+
+    // The start has equal weighted discs and four moves of equal weight
+    let (value, priors) = net.evaluate(&Board::new(8, 8));
+    assert!((value - 0.5).abs() < 1e-6);
+    assert!(priors.iter().all(|(_, p)| (p - 0.25).abs() < 1e-6));
+}
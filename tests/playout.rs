@@ -0,0 +1,14 @@
+// Parsing of playout policy names and their parameters
+use reversi::playout::policy_from_name;
+
+#[test]
+fn policy_parameters_are_checked() {
+    for name in ["uniform", "epsilon-greedy", "epsilon-greedy:0", "epsilon-greedy:1", "softmax", "softmax:0.5"].iter() {
+        assert!(policy_from_name(name).is_ok(), "{}", name);
+    }
+
+    for name in ["epsilon-greedy:-0.1", "epsilon-greedy:1.5", "epsilon-greedy:NaN", "softmax:0", "softmax:-2",
+                 "softmax:inf", "softmax:x", "random"].iter() {
+        assert!(policy_from_name(name).is_err(), "{}", name);
+    }
+}
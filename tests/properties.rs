@@ -0,0 +1,142 @@
+// Property tests: random games and positions never break the rules engine's invariants
+mod common;
+
+use common::{flips, legal_moves, sorted};
+use proptest::prelude::*;
+use reversi::{convert_num, flipped, parse_pos, Board, GameRecord};
+
+/**
+ * Checks a position against the reference rules: valid squares, a consistent perimeter and
+ * action sets, and both sides' actions matching legal_moves()
+ */
+fn check_position(b: &Board) -> Result<(), TestCaseError> {
+    prop_assert!(b.squares().iter().all(|val| *val <= 2));
+    prop_assert_eq!(b.check_consistency(), Ok(()));
+    prop_assert_eq!(sorted(&b.get_player_actions()), legal_moves(b.squares(), 1));
+    prop_assert_eq!(sorted(&b.get_cpu_actions()), legal_moves(b.squares(), 2));
+
+    let side: Vec<u8> = sorted(&b.get_available_actions(false));
+    let expected = if b.is_player_turn() { b.get_player_actions() } else { b.get_cpu_actions() };
+    prop_assert_eq!(side, sorted(&expected));
+    Ok(())
+}
+
+/**
+ * Plays a game from the start, choosing each move from choices, and checks every position
+ * along the way
+ *
+ *  @returns:   the record of the game and its final position
+ */
+fn random_game(choices: &[usize]) -> Result<(GameRecord, Board), TestCaseError> {
+    let mut record = GameRecord::new();
+    let mut board = record.start();
+    check_position(&board)?;
+
+    for choice in choices {
+        let actions = board.get_available_actions(false);
+        if actions.is_empty() {
+            let opponent = if board.is_player_turn() { board.get_cpu_actions() } else { board.get_player_actions() };
+            if opponent.is_empty() {
+                break;
+            }
+            prop_assert!(record.pass(&mut board).is_ok());
+            check_position(&board)?;
+            continue;
+        }
+
+        let pos = *actions.get_index(choice % actions.len()).unwrap();
+        let val: u8 = if board.is_player_turn() { 1 } else { 2 };
+        let before = board.clone();
+        let (mover, opponent) = if val == 1 { before.get_score() } else { swap(before.get_score()) };
+        let expected_flips = flips(before.squares(), pos, val);
+
+        prop_assert!(record.play(&mut board, pos).is_ok());
+
+        // Only the move's square is filled, exactly the reference flips change colour and the
+        // disc counts move by them
+        prop_assert_eq!(board.squares()[pos as usize], val);
+        prop_assert_eq!(flipped(&before, &board), expected_flips.clone());
+        for (old, new) in before.squares().iter().zip(board.squares().iter()) {
+            prop_assert!(*old == 0 || *new != 0);
+        }
+        let flips = expected_flips.len() as u8;
+        let (new_mover, new_opponent) = if val == 1 { board.get_score() } else { swap(board.get_score()) };
+        prop_assert_eq!(new_mover, mover + flips + 1);
+        prop_assert_eq!(new_opponent, opponent - flips);
+        prop_assert_eq!(board.is_player_turn(), val == 2);
+
+        check_position(&board)?;
+    }
+
+    Ok((record, board))
+}
+
+fn swap((a, b): (u8, u8)) -> (u8, u8) {
+    (b, a)
+}
+
+proptest! {
+
+    #[test]
+    fn random_games_stay_legal(choices in prop::collection::vec(any::<usize>(), 0..70)) {
+        random_game(&choices)?;
+    }
+
+    #[test]
+    fn random_positions_match_the_reference_rules(squares in prop::collection::vec(0_u8..=2, 64), player_turn in any::<bool>()) {
+        let b = Board::from_squares(&squares, player_turn).unwrap();
+        check_position(&b)?;
+
+        let state = b.check_game_state(false);
+        let over = b.get_player_actions().is_empty() || b.get_cpu_actions().is_empty();
+        prop_assert_eq!(state != 0, over);
+    }
+
+    #[test]
+    fn positions_round_trip_through_obf(choices in prop::collection::vec(any::<usize>(), 0..70)) {
+        let (_, board) = random_game(&choices)?;
+        let parsed = Board::from_obf(&board.to_obf()).unwrap();
+
+        prop_assert_eq!(parsed.squares(), board.squares());
+        prop_assert_eq!(parsed.is_player_turn(), board.is_player_turn());
+        prop_assert_eq!(sorted(&parsed.get_available_actions(false)), sorted(&board.get_available_actions(false)));
+    }
+
+    #[test]
+    fn records_round_trip(choices in prop::collection::vec(any::<usize>(), 0..70)) {
+        let (record, board) = random_game(&choices)?;
+        let parsed = GameRecord::parse(&record.to_string()).unwrap();
+
+        prop_assert_eq!(parsed.moves(), record.moves());
+        let end = parsed.board();
+        prop_assert_eq!(end.squares(), board.squares());
+    }
+
+    #[test]
+    fn records_from_a_position_round_trip(choices in prop::collection::vec(any::<usize>(), 0..20), more in prop::collection::vec(any::<usize>(), 0..20)) {
+        let (_, start) = random_game(&choices)?;
+        let mut record = GameRecord::from_position(&start);
+        let mut board = start.clone();
+
+        for choice in more {
+            let actions = board.get_available_actions(false);
+            if actions.is_empty() {
+                break;
+            }
+            prop_assert!(record.play(&mut board, *actions.get_index(choice % actions.len()).unwrap()).is_ok());
+        }
+
+        let parsed = GameRecord::parse(&record.to_string()).unwrap();
+        let (parsed_start, end) = (parsed.start(), parsed.board());
+        prop_assert_eq!(parsed_start.squares(), start.squares());
+        prop_assert_eq!(parsed.moves(), record.moves());
+        prop_assert_eq!(end.squares(), board.squares());
+    }
+
+    #[test]
+    fn square_names_round_trip(s in "[a-zA-Z0-9]{0,3}") {
+        if let Some(pos) = parse_pos(&s) {
+            prop_assert!(convert_num(pos).eq_ignore_ascii_case(&s));
+        }
+    }
+}
@@ -0,0 +1,103 @@
+// Flat Monte Carlo and PUCT searches on small known positions
+mod common;
+
+use std::time::Duration;
+
+use common::position;
+use reversi::clock::MoveBudget;
+use reversi::playout::{PlayoutPolicies, Uniform};
+use reversi::tree::PuctConfig;
+use reversi::{parse_pos, Board, Searcher};
+
+fn searcher(puct: Option<PuctConfig>) -> Searcher<'static> {
+    Searcher {
+        policies: PlayoutPolicies { player: &Uniform, cpu: &Uniform },
+        network: None,
+        puct,
+        progress: Some(&|_| ()),
+        stop: None
+    }
+}
+
+/**
+ * The Player (X) has no discs and so no moves
+ */
+fn no_moves() -> Board {
+    position(["OOO-----", "--------", "--------", "--------", "--------", "--------", "--------", "--------"], 'X')
+}
+
+#[test]
+fn no_result_without_moves() {
+    let budget = MoveBudget::fixed(1);
+
+    assert!(searcher(None).search(&no_moves(), &budget, false).is_none());
+    assert!(searcher(Some(PuctConfig::default())).search(&no_moves(), &budget, false).is_none());
+}
+
+/**
+ * The Player (X) has two moves: B8 takes the last empty squares of row 8 and ends the game
+ * with X ahead, after G1 every reply ends it with O ahead
+ */
+fn win_or_lose() -> Board {
+    position([
+        "XXXXXX-X",
+        "XXXXOOOO",
+        "XOXOXOOO",
+        "XOXOOXOO",
+        "XOXOOOXO",
+        "XOOXOOXO",
+        "XOOOOOOO",
+        "X-XXXXOO",
+    ], 'X')
+}
+
+#[test]
+fn winning_move_outranks_losing_move() {
+    let (win, lose) = (parse_pos("b8").unwrap(), parse_pos("g1").unwrap());
+    let time = Duration::from_millis(300);
+    let budget = MoveBudget { target: time, maximum: time };
+
+    for puct in [None, Some(PuctConfig::default())].iter() {
+        let result = searcher(puct.clone()).search(&win_or_lose(), &budget, false).unwrap();
+        let stats = |action: u8| result.moves.iter().find(|m| m.action == action).unwrap();
+
+        assert_eq!(result.best, win);
+        assert_eq!(result.moves[0].action, win);
+        assert!(stats(win).value > 0.99, "{}", stats(win).value);
+        assert!(stats(lose).value < 0.01, "{}", stats(lose).value);
+
+        // Every finished playout through a move agrees with its result
+        assert_eq!((stats(win).draws, stats(win).losses), (0, 0));
+        assert_eq!((stats(lose).wins, stats(lose).draws), (0, 0));
+        assert!(stats(win).disc_diff > 0.0 && stats(lose).disc_diff < 0.0);
+    }
+}
+
+/**
+ * win_or_lose() mirrored top to bottom, so the winning move B1 comes before the losing G8
+ */
+fn win_or_lose_mirrored() -> Board {
+    position([
+        "X-XXXXOO",
+        "XOOOOOOO",
+        "XOOXOOXO",
+        "XOXOOOXO",
+        "XOXOOXOO",
+        "XOXOXOOO",
+        "XXXXOOOO",
+        "XXXXXX-X",
+    ], 'X')
+}
+
+#[test]
+fn low_temperature_plays_the_most_visited_move() {
+    let time = Duration::from_millis(300);
+    let budget = MoveBudget { target: time, maximum: time };
+
+    // visits^(1 / temperature) overflows f32 after a few hundred visits unless the visits are scaled
+    for temperature in [0.0, 0.0001, 0.01, 0.1].iter() {
+        let config = PuctConfig { temperature: *temperature, ..PuctConfig::default() };
+        let result = searcher(Some(config)).search(&win_or_lose_mirrored(), &budget, false).unwrap();
+        assert_eq!(result.best, parse_pos("b1").unwrap(), "temperature {}", temperature);
+    }
+}